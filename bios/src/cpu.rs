@@ -0,0 +1,35 @@
+use core::arch::asm;
+
+/// Reads a byte from an I/O port.
+///
+/// # Safety
+///
+/// Reading some ports has side effects on the device behind them.
+#[inline]
+pub unsafe fn inb(port: u16) -> u8 {
+    let value: u8;
+    unsafe { asm!("in al, dx", in("dx") port, out("al") value, options(nomem, nostack, preserves_flags)) };
+    value
+}
+
+/// Writes a byte to an I/O port.
+///
+/// # Safety
+///
+/// The device behind the port may do anything, including overwriting memory through DMA.
+#[inline]
+pub unsafe fn outb(port: u16, value: u8) {
+    unsafe { asm!("out dx, al", in("dx") port, in("al") value, options(nomem, nostack, preserves_flags)) };
+}
+
+/// Runs `f` with maskable interrupts disabled, then restores the interrupt flag to what it was.
+#[inline]
+pub fn without_interrupts<R>(f: impl FnOnce() -> R) -> R {
+    let flags: u16;
+    unsafe { asm!("pushf", "pop {0:x}", "cli", out(reg) flags) };
+
+    let result = f();
+
+    unsafe { asm!("push {0:x}", "popf", in(reg) flags) };
+    result
+}
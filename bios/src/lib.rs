@@ -1,3 +1,10 @@
 #![no_std]
 
+mod registers;
+
+pub mod cpu;
+pub mod pit;
 pub mod rtc;
+pub mod system;
+
+pub use registers::{Registers, int};
@@ -0,0 +1,140 @@
+use crate::cpu::{inb, outb, without_interrupts};
+use crate::rtc::peek_ticks;
+
+/// The input clock of the programmable interval timer, in hertz.
+pub const FREQUENCY: u32 = 1_193_182;
+
+/// Input clocks per BIOS tick, the period the BIOS programs.
+pub const BIOS_DIVISOR: u32 = 0x10000;
+
+const CHANNEL_0: u16 = 0x40;
+const COMMAND: u16 = 0x43;
+/// Command and status port of the master interrupt controller.
+const PIC: u16 = 0x20;
+
+/// The period of channel 0 in input clocks, or `0` while it still runs as the BIOS left it.
+static mut DIVISOR: u32 = 0;
+
+/// Input clocks from the last BIOS tick to the last timer interrupt, see [`advance_phase`].
+static mut PHASE: u32 = 0;
+
+/// Programs channel 0, which drives IRQ 0 and the BIOS tick count, as a rate generator
+/// (mode 2) firing every `divisor` input clocks, `0` meaning 65536.
+///
+/// The BIOS programs mode 3, in which the counter steps by two and runs down twice per period,
+/// so [`counter`] cannot tell where in the period it is. Mode 2 with a divisor of 65536 keeps
+/// the BIOS rate of about 18.2 Hz. With any other divisor, the timer interrupt handler must
+/// call [`advance_phase`] and only pass on every tick it reports as due to the BIOS.
+pub fn set_divisor(divisor: u16) {
+    without_interrupts(|| unsafe {
+        // Channel 0, low byte then high byte, mode 2, binary.
+        outb(COMMAND, 0x34);
+        outb(CHANNEL_0, divisor as u8);
+        outb(CHANNEL_0, (divisor >> 8) as u8);
+
+        DIVISOR = if divisor == 0 { BIOS_DIVISOR } else { divisor as u32 };
+        PHASE = 0;
+    });
+}
+
+/// Programs channel 0 back the way the BIOS leaves it, as a square wave generator (mode 3)
+/// with a divisor of 65536, undoing [`set_divisor`].
+pub fn restore() {
+    without_interrupts(|| unsafe {
+        // Channel 0, low byte then high byte, mode 3, binary.
+        outb(COMMAND, 0x36);
+        outb(CHANNEL_0, 0);
+        outb(CHANNEL_0, 0);
+
+        DIVISOR = 0;
+        PHASE = 0;
+    });
+}
+
+/// Returns the period of channel 0 in input clocks, or [`None`] if it has not been programmed
+/// through [`set_divisor`].
+#[inline]
+#[must_use]
+pub fn divisor() -> Option<u32> {
+    match unsafe { DIVISOR } {
+        0 => None,
+        divisor => Some(divisor),
+    }
+}
+
+/// Accounts for one timer interrupt and returns `true` if a BIOS tick is due, which happens
+/// once every [`BIOS_DIVISOR`] input clocks.
+///
+/// # Safety
+///
+/// Must be called exactly once per timer interrupt, from the interrupt handler.
+#[inline]
+pub unsafe fn advance_phase() -> bool {
+    unsafe {
+        PHASE += DIVISOR;
+
+        if PHASE >= BIOS_DIVISOR {
+            PHASE -= BIOS_DIVISOR;
+            true
+        } else {
+            false
+        }
+    }
+}
+
+/// Returns the current count of channel 0, which runs down from the divisor to one.
+#[must_use]
+pub fn counter() -> u16 {
+    without_interrupts(|| unsafe {
+        // Latch channel 0 so both bytes come from the same instant.
+        outb(COMMAND, 0x00);
+        let low = inb(CHANNEL_0);
+        let high = inb(CHANNEL_0);
+        (high as u16) << 8 | low as u16
+    })
+}
+
+/// A consistent reading of the BIOS tick count and channel 0.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Snapshot {
+    /// The BIOS ticks since midnight. May equal [`TICKS_PER_DAY`] right at midnight.
+    ///
+    /// [`TICKS_PER_DAY`]: crate::rtc::TICKS_PER_DAY
+    pub ticks: u32,
+    /// Whether the BIOS midnight flag is set, see [`peek_ticks`].
+    pub midnight: bool,
+    /// Input clocks elapsed since the last BIOS tick.
+    pub elapsed: u16,
+}
+
+/// Reads the BIOS tick count and channel 0 together.
+///
+/// If the counter has reloaded but the timer interrupt has not been serviced yet, the tick
+/// count is one period behind the counter. That is detected through the interrupt controller
+/// and corrected, so successive snapshots never go backwards.
+///
+/// Channel 0 must have been programmed through [`set_divisor`], or `elapsed` is meaningless.
+#[must_use]
+pub fn snapshot() -> Snapshot {
+    without_interrupts(|| {
+        let divisor = divisor().unwrap_or(BIOS_DIVISOR);
+        let count = counter();
+        let (mut ticks, midnight) = peek_ticks();
+
+        // Read the interrupt request register and check for a pending IRQ 0.
+        let pending = unsafe {
+            outb(PIC, 0x0A);
+            inb(PIC) & 1 != 0
+        };
+
+        let mut elapsed = unsafe { PHASE } + (divisor - count as u32) % divisor;
+
+        // Early in the period with IRQ 0 pending means the reload happened before the latch.
+        if pending && (divisor - count as u32) % divisor < divisor / 2 {
+            elapsed += divisor;
+        }
+
+        ticks += elapsed / BIOS_DIVISOR;
+        Snapshot { ticks, midnight, elapsed: (elapsed % BIOS_DIVISOR) as u16 }
+    })
+}
@@ -0,0 +1,188 @@
+use core::arch::asm;
+use core::mem::offset_of;
+
+/// The register set passed to and returned from a real mode service routine.
+///
+/// Construct the inputs with struct update syntax on top of [`Registers::new`], which points
+/// `DS` and `ES` at the current data segment:
+///
+/// ```no_run
+/// use bios::Registers;
+///
+/// let mut regs = Registers { ax: 0x3000, ..Registers::new() };
+/// unsafe { bios::int::<0x21>(&mut regs) };
+/// let (major, minor) = (regs.al(), regs.ah());
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[repr(C)]
+pub struct Registers {
+    pub ax: u16,
+    pub bx: u16,
+    pub cx: u16,
+    pub dx: u16,
+    pub si: u16,
+    pub di: u16,
+    pub ds: u16,
+    pub es: u16,
+    /// FLAGS as returned by the service routine. Ignored on input.
+    pub flags: u16,
+}
+
+impl Registers {
+    /// Carry flag.
+    pub const CF: u16 = 1 << 0;
+    /// Zero flag.
+    pub const ZF: u16 = 1 << 6;
+
+    /// Returns a register set with all general purpose registers zeroed and `DS`/`ES`
+    /// pointing at the current data segment.
+    #[inline]
+    #[must_use]
+    pub fn new() -> Self {
+        let ds: u16;
+        unsafe { asm!("mov {0:x}, ds", out(reg) ds, options(nomem, nostack, preserves_flags)) };
+        Self { ax: 0, bx: 0, cx: 0, dx: 0, si: 0, di: 0, ds, es: ds, flags: 0 }
+    }
+
+    #[inline]
+    #[must_use]
+    pub const fn ah(&self) -> u8 {
+        (self.ax >> 8) as u8
+    }
+
+    #[inline]
+    #[must_use]
+    pub const fn al(&self) -> u8 {
+        self.ax as u8
+    }
+
+    #[inline]
+    #[must_use]
+    pub const fn bh(&self) -> u8 {
+        (self.bx >> 8) as u8
+    }
+
+    #[inline]
+    #[must_use]
+    pub const fn bl(&self) -> u8 {
+        self.bx as u8
+    }
+
+    #[inline]
+    #[must_use]
+    pub const fn ch(&self) -> u8 {
+        (self.cx >> 8) as u8
+    }
+
+    #[inline]
+    #[must_use]
+    pub const fn cl(&self) -> u8 {
+        self.cx as u8
+    }
+
+    #[inline]
+    #[must_use]
+    pub const fn dh(&self) -> u8 {
+        (self.dx >> 8) as u8
+    }
+
+    #[inline]
+    #[must_use]
+    pub const fn dl(&self) -> u8 {
+        self.dx as u8
+    }
+
+    /// Returns `true` if the service routine returned with the carry flag set,
+    /// which most DOS and BIOS services use to report failure.
+    #[inline]
+    #[must_use]
+    pub const fn carry(&self) -> bool {
+        self.flags & Self::CF != 0
+    }
+}
+
+impl Default for Registers {
+    #[inline]
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Raises software interrupt `N` with `regs` loaded into the CPU, then stores the registers
+/// and FLAGS the service routine returned with back into `regs`.
+///
+/// Everything happens in a single `asm!` block, so the carry flag observed through
+/// [`Registers::carry`] is exactly the one the service routine returned with. The carry flag
+/// is cleared before the interrupt is raised, so services that do not report errors through
+/// it come back with it clear.
+///
+/// # Safety
+///
+/// The service routine may read and write arbitrary memory, in particular through the
+/// segment:offset pairs held in `regs`. The caller must uphold whatever contract the
+/// requested service has.
+#[inline]
+pub unsafe fn int<const N: u8>(regs: &mut Registers) {
+    unsafe {
+        asm!(
+            // Save what the service routine may clobber but the compiler relies on.
+            "push ebp",
+            "push ds",
+            "push es",
+            "push esi",
+            "push bx",
+            // Load the inputs. `BX` is the pointer to `regs`, so it goes last, and the segment
+            // registers are loaded from the stack once `regs` can no longer be reached.
+            "push word ptr [bx + {ds}]",
+            "push word ptr [bx + {es}]",
+            "mov ax, word ptr [bx + {ax}]",
+            "mov cx, word ptr [bx + {cx}]",
+            "mov dx, word ptr [bx + {dx}]",
+            "mov si, word ptr [bx + {si}]",
+            "mov di, word ptr [bx + {di}]",
+            "mov bx, word ptr [bx + {bx}]",
+            "pop es",
+            "pop ds",
+            "clc",
+            "int {n}",
+            // Capture FLAGS first, then get back our own `DS` and the pointer to `regs`.
+            "pushf",
+            "push ds",
+            "push es",
+            "push bx",
+            "mov bx, sp",
+            "mov ds, word ptr ss:[bx + 16]",
+            "mov bx, word ptr ss:[bx + 8]",
+            "mov word ptr [bx + {ax}], ax",
+            "mov word ptr [bx + {cx}], cx",
+            "mov word ptr [bx + {dx}], dx",
+            "mov word ptr [bx + {si}], si",
+            "mov word ptr [bx + {di}], di",
+            "pop word ptr [bx + {bx}]",
+            "pop word ptr [bx + {es}]",
+            "pop word ptr [bx + {ds}]",
+            "pop word ptr [bx + {flags}]",
+            "add sp, 2",
+            "pop esi",
+            "pop es",
+            "pop ds",
+            "pop ebp",
+            "cld",
+            n = const N,
+            ax = const offset_of!(Registers, ax),
+            bx = const offset_of!(Registers, bx),
+            cx = const offset_of!(Registers, cx),
+            dx = const offset_of!(Registers, dx),
+            si = const offset_of!(Registers, si),
+            di = const offset_of!(Registers, di),
+            ds = const offset_of!(Registers, ds),
+            es = const offset_of!(Registers, es),
+            flags = const offset_of!(Registers, flags),
+            inout("bx") regs as *mut Registers => _,
+            out("ax") _,
+            out("cx") _,
+            out("dx") _,
+            out("di") _,
+        )
+    }
+}
@@ -1,8 +1,278 @@
+use crate::{Registers, int};
 use core::arch::asm;
+use core::fmt::{self, Display};
 
+/// Timer ticks in a day. The counter resets to zero when it reaches this value.
+pub const TICKS_PER_DAY: u32 = 0x1800B0;
+
+/// Returns the timer ticks since midnight, at about 18.2 per second.
+///
+/// The midnight flag is left alone, see [`peek_ticks`].
 #[inline]
+#[must_use]
 pub fn ticks() -> u32 {
-    let (hi, lo): (u16, u16);
-    unsafe { asm!("int 0x1A", in("ah") 0u8, lateout("cx") hi, lateout("dx") lo, options(nomem, nostack)) };
-    (hi as u32) << 16 | lo as u32
+    peek_ticks().0
+}
+
+/// Returns the timer ticks since midnight and whether midnight has passed since the flag was
+/// last cleared, reading both straight from the BIOS data area at 0040:006Ch.
+///
+/// Unlike [`read_ticks`], this does not clear the midnight flag, which DOS relies on to advance
+/// its date.
+#[must_use]
+pub fn peek_ticks() -> (u32, bool) {
+    let (low, high): (u16, u16);
+    let midnight: u8;
+
+    unsafe {
+        asm!(
+            "push es",
+            "mov {segment:x}, 0x40",
+            "mov es, {segment:x}",
+            // The timer interrupt must not update the counter halfway through the read.
+            "pushf",
+            "cli",
+            "mov {low:x}, word ptr es:[0x6C]",
+            "mov {high:x}, word ptr es:[0x6E]",
+            "mov {midnight}, byte ptr es:[0x70]",
+            "popf",
+            "pop es",
+            segment = out(reg) _,
+            low = out(reg) low,
+            high = out(reg) high,
+            midnight = out(reg_byte) midnight,
+        )
+    };
+
+    ((high as u32) << 16 | low as u32, midnight != 0)
+}
+
+/// Returns the timer ticks since midnight and whether midnight has passed since the last
+/// call (int 1Ah, AH=00h).
+///
+/// The BIOS clears the midnight flag on this call. DOS learns about the new day through that
+/// flag, so a caller that sees it set must advance the DOS date itself. Prefer [`peek_ticks`].
+#[must_use]
+pub fn read_ticks() -> (u32, bool) {
+    let mut regs = Registers { ax: 0x0000, ..Registers::new() };
+    unsafe { int::<0x1A>(&mut regs) };
+    ((regs.cx as u32) << 16 | regs.dx as u32, regs.al() != 0)
+}
+
+/// Sets the timer ticks since midnight (int 1Ah, AH=01h) and clears the midnight flag.
+#[inline]
+pub fn set_ticks(ticks: u32) {
+    let mut regs = Registers { ax: 0x0100, cx: (ticks >> 16) as u16, dx: ticks as u16, ..Registers::new() };
+    unsafe { int::<0x1A>(&mut regs) };
+}
+
+/// The time of day kept by the real-time clock.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Time {
+    pub hour: u8,
+    pub minute: u8,
+    pub second: u8,
+    /// Whether the clock applies daylight saving time.
+    pub daylight_saving: bool,
+}
+
+/// The date kept by the real-time clock.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Date {
+    pub year: u16,
+    pub month: u8,
+    pub day: u8,
+}
+
+/// Reads the time of the real-time clock (int 1Ah, AH=02h).
+///
+/// Fails if the clock is not running, or is updating and returns digits that are not BCD.
+pub fn time() -> Result<Time, ClockError> {
+    let mut regs = Registers { ax: 0x0200, ..Registers::new() };
+    unsafe { int::<0x1A>(&mut regs) };
+
+    if regs.carry() {
+        return Err(ClockError);
+    }
+
+    decode_time(regs.cx, regs.dx).ok_or(ClockError)
+}
+
+/// Reads the date of the real-time clock (int 1Ah, AH=04h).
+///
+/// Fails if the clock is not running, or is updating and returns digits that are not BCD.
+pub fn date() -> Result<Date, ClockError> {
+    let mut regs = Registers { ax: 0x0400, ..Registers::new() };
+    unsafe { int::<0x1A>(&mut regs) };
+
+    if regs.carry() {
+        return Err(ClockError);
+    }
+
+    decode_date(regs.cx, regs.dx).ok_or(ClockError)
+}
+
+/// Sets the alarm of the real-time clock (int 1Ah, AH=06h), which raises int 4Ah once a day
+/// at the given time.
+///
+/// Fails if an alarm is already set, see [`reset_alarm`], or if the clock is not running.
+pub fn set_alarm(hour: u8, minute: u8, second: u8) -> Result<(), ClockError> {
+    let (cx, dx) = encode_time(hour, minute, second);
+    let mut regs = Registers { ax: 0x0600, cx, dx, ..Registers::new() };
+    unsafe { int::<0x1A>(&mut regs) };
+
+    if regs.carry() { Err(ClockError) } else { Ok(()) }
+}
+
+/// Cancels the alarm of the real-time clock (int 1Ah, AH=07h).
+#[inline]
+pub fn reset_alarm() {
+    let mut regs = Registers { ax: 0x0700, ..Registers::new() };
+    unsafe { int::<0x1A>(&mut regs) };
+}
+
+/// Sets the time of the real-time clock (int 1Ah, AH=03h), with daylight saving disabled.
+///
+/// The values are binary; they are encoded as BCD for the clock. DOS keeps its own clock,
+/// which this does not change.
+pub fn set_time(hour: u8, minute: u8, second: u8) -> Result<(), ClockError> {
+    let (cx, dx) = encode_time(hour, minute, second);
+    let mut regs = Registers { ax: 0x0300, cx, dx, ..Registers::new() };
+    unsafe { int::<0x1A>(&mut regs) };
+
+    if regs.carry() { Err(ClockError) } else { Ok(()) }
+}
+
+/// Sets the date of the real-time clock (int 1Ah, AH=05h).
+///
+/// The values are binary; they are encoded as BCD for the clock. DOS keeps its own clock,
+/// which this does not change.
+pub fn set_date(year: u16, month: u8, day: u8) -> Result<(), ClockError> {
+    let (cx, dx) = encode_date(year, month, day);
+    let mut regs = Registers { ax: 0x0500, cx, dx, ..Registers::new() };
+    unsafe { int::<0x1A>(&mut regs) };
+
+    if regs.carry() { Err(ClockError) } else { Ok(()) }
+}
+
+/// Unpacks the time of day in CX and DX as the clock returns it, see [`encode_time`], or
+/// returns [`None`] if a field is not BCD. Bit 0 of DL is the daylight saving flag.
+const fn decode_time(cx: u16, dx: u16) -> Option<Time> {
+    match (from_bcd((cx >> 8) as u8), from_bcd(cx as u8), from_bcd((dx >> 8) as u8)) {
+        (Some(hour), Some(minute), Some(second)) => Some(Time { hour, minute, second, daylight_saving: dx & 1 != 0 }),
+        _ => None,
+    }
+}
+
+/// Unpacks the date in CX and DX as the clock returns it, see [`encode_date`], or returns
+/// [`None`] if a field is not BCD.
+const fn decode_date(cx: u16, dx: u16) -> Option<Date> {
+    match (from_bcd((cx >> 8) as u8), from_bcd(cx as u8), from_bcd((dx >> 8) as u8), from_bcd(dx as u8)) {
+        (Some(century), Some(year), Some(month), Some(day)) => Some(Date { year: century as u16 * 100 + year as u16, month, day }),
+        _ => None,
+    }
+}
+
+/// Packs a time of day into CX and DX as the clock takes it: CH hour, CL minute and DH second,
+/// all BCD, and DL 0 for no daylight saving.
+const fn encode_time(hour: u8, minute: u8, second: u8) -> (u16, u16) {
+    ((to_bcd(hour) as u16) << 8 | to_bcd(minute) as u16, (to_bcd(second) as u16) << 8)
+}
+
+/// Packs a date into CX and DX as the clock takes it: CH century, CL year, DH month and DL
+/// day, all BCD.
+const fn encode_date(year: u16, month: u8, day: u8) -> (u16, u16) {
+    let (century, year) = ((year / 100) as u8, (year % 100) as u8);
+    ((to_bcd(century) as u16) << 8 | to_bcd(year) as u16, (to_bcd(month) as u16) << 8 | to_bcd(day) as u16)
+}
+
+/// The error returned when the real-time clock is not running or is missing, as on the
+/// original PC and XT, or when it rejects a request.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct ClockError;
+
+impl Display for ClockError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("real-time clock not operating")
+    }
+}
+
+impl core::error::Error for ClockError {}
+
+/// Decodes a packed binary-coded decimal byte as used by the real-time clock, or returns
+/// [`None`] if either nibble is not a decimal digit.
+#[inline]
+#[must_use]
+pub const fn from_bcd(value: u8) -> Option<u8> {
+    let (high, low) = (value >> 4, value & 0x0F);
+    if high < 10 && low < 10 { Some(high * 10 + low) } else { None }
+}
+
+/// Encodes a value below 100 as a packed binary-coded decimal byte.
+#[inline]
+#[must_use]
+pub const fn to_bcd(value: u8) -> u8 {
+    ((value / 10) << 4) | (value % 10)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn bcd_round_trip() {
+        for value in 0..100 {
+            assert_eq!(from_bcd(to_bcd(value)), Some(value));
+        }
+
+        assert_eq!(to_bcd(59), 0x59);
+        assert_eq!(from_bcd(0x99), Some(99));
+    }
+
+    #[test]
+    fn encode_time_packs_bcd() {
+        assert_eq!(encode_time(0, 0, 0), (0x0000, 0x0000));
+        assert_eq!(encode_time(13, 45, 31), (0x1345, 0x3100));
+        assert_eq!(encode_time(23, 59, 59), (0x2359, 0x5900));
+    }
+
+    #[test]
+    fn encode_date_packs_bcd() {
+        assert_eq!(encode_date(1980, 1, 1), (0x1980, 0x0101));
+        assert_eq!(encode_date(1999, 12, 31), (0x1999, 0x1231));
+        assert_eq!(encode_date(2000, 2, 29), (0x2000, 0x0229));
+        assert_eq!(encode_date(2107, 12, 31), (0x2107, 0x1231));
+    }
+
+    #[test]
+    fn decode_time_round_trips() {
+        let time = Time { hour: 13, minute: 45, second: 31, daylight_saving: false };
+        let (cx, dx) = encode_time(13, 45, 31);
+        assert_eq!(decode_time(cx, dx), Some(time));
+        assert_eq!(decode_time(cx, dx | 1), Some(Time { daylight_saving: true, ..time }));
+    }
+
+    #[test]
+    fn decode_time_rejects_an_updating_clock() {
+        assert_eq!(decode_time(0x13FF, 0x3100), None);
+        assert_eq!(decode_time(0x1345, 0x3A00), None);
+    }
+
+    #[test]
+    fn decode_date_round_trips() {
+        for (year, month, day) in [(1980, 1, 1), (1999, 12, 31), (2000, 2, 29), (2107, 12, 31)] {
+            let (cx, dx) = encode_date(year, month, day);
+            assert_eq!(decode_date(cx, dx), Some(Date { year, month, day }));
+        }
+
+        assert_eq!(decode_date(0x1A80, 0x0101), None);
+        assert_eq!(decode_date(0x1980, 0x01F1), None);
+    }
+
+    #[test]
+    fn from_bcd_rejects_hex_digits() {
+        assert_eq!(from_bcd(0x0A), None);
+        assert_eq!(from_bcd(0xA0), None);
+        assert_eq!(from_bcd(0xFF), None);
+    }
 }
@@ -0,0 +1,25 @@
+use crate::{Registers, int};
+use core::fmt::{self, Display};
+
+/// Waits for `microseconds` (int 15h, AH=86h).
+///
+/// The BIOS counts in units of 976 µs, so waits are rounded to those. Fails without waiting
+/// on machines that lack the call, such as the PC and XT, or when a wait is already in progress.
+pub fn wait(microseconds: u32) -> Result<(), WaitError> {
+    let mut regs = Registers { ax: 0x8600, cx: (microseconds >> 16) as u16, dx: microseconds as u16, ..Registers::new() };
+    unsafe { int::<0x15>(&mut regs) };
+
+    if regs.carry() { Err(WaitError) } else { Ok(()) }
+}
+
+/// The error returned when the BIOS cannot wait.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct WaitError;
+
+impl Display for WaitError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("BIOS wait not available")
+    }
+}
+
+impl core::error::Error for WaitError {}
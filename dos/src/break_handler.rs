@@ -0,0 +1,120 @@
+use crate::interrupt::{Action, InterruptFrame, InterruptGuard};
+use crate::process::{add_exit_hook, remove_exit_hook};
+use bios::Registers;
+use bios::cpu::without_interrupts;
+use core::sync::atomic::{AtomicBool, Ordering};
+
+/// What happens when the user presses Ctrl-C or Ctrl-Break.
+#[derive(Debug, Clone, Copy)]
+pub enum Policy {
+    /// Nothing, the program carries on.
+    Ignore,
+    /// A flag is set, to be polled with [`take_pressed`].
+    Flag,
+    /// The function is called, then the program carries on.
+    ///
+    /// It runs from within DOS, on the stack of the interrupted call, and may call any DOS
+    /// function, including [`process::exit`] to end the program cleanly.
+    ///
+    /// [`process::exit`]: crate::process::exit
+    Callback(fn()),
+}
+
+/// An error returned when the break handler cannot be installed.
+#[derive(thiserror::Error, Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum BreakError {
+    /// All exit hook slots are taken.
+    #[error("no free slot")]
+    NoFreeSlot,
+}
+
+static mut POLICY: Policy = Policy::Ignore;
+
+static PRESSED: AtomicBool = AtomicBool::new(false);
+
+/// The exit hook restoring the handler, or [`None`] if it is not installed.
+static mut EXIT_HOOK: Option<usize> = None;
+
+/// Keeps [`HANDLER`] installed, taken by [`restore`].
+static mut GUARD: Option<InterruptGuard> = None;
+
+crate::interrupt_handler! {
+    /// The int 23h handler. DOS calls it on the stack of the program, so it needs none of its own.
+    static HANDLER = on_break;
+}
+
+/// Handles Ctrl-C and Ctrl-Break according to `policy` (int 23h) instead of terminating the
+/// program, replacing the policy if already installed.
+///
+/// The original handler is put back by [`restore`], or on exit through [`process::exit`] or by
+/// returning from `main`.
+///
+/// [`process::exit`]: crate::process::exit
+pub fn install(policy: Policy) -> Result<(), BreakError> {
+    without_interrupts(|| {
+        unsafe { POLICY = policy };
+
+        if unsafe { EXIT_HOOK }.is_some() {
+            return Ok(());
+        }
+
+        let exit_hook = add_exit_hook(uninstall).ok_or(BreakError::NoFreeSlot)?;
+
+        unsafe {
+            GUARD = Some(InterruptGuard::install(0x23, &HANDLER));
+            EXIT_HOOK = Some(exit_hook);
+        }
+
+        Ok(())
+    })
+}
+
+/// Puts back the handler that was installed before [`install`], so Ctrl-C terminates the
+/// program again.
+pub fn restore() {
+    if let Some(exit_hook) = unsafe { EXIT_HOOK } {
+        remove_exit_hook(exit_hook);
+        uninstall();
+    }
+}
+
+/// Returns whether Ctrl-C or Ctrl-Break was pressed since the last call, and clears the flag.
+/// Only set under [`Policy::Flag`].
+#[inline]
+pub fn take_pressed() -> bool {
+    PRESSED.swap(false, Ordering::Relaxed)
+}
+
+/// Returns whether DOS checks for Ctrl-C on every call, rather than only during character
+/// I/O (int 21h, AX=3300h).
+#[must_use]
+pub fn checking() -> bool {
+    let mut regs = Registers { ax: 0x3300, ..Registers::new() };
+    unsafe { bios::int::<0x21>(&mut regs) };
+    regs.dl() != 0
+}
+
+/// Sets whether DOS checks for Ctrl-C on every call (int 21h, AX=3301h), as the `BREAK`
+/// command does.
+pub fn set_checking(enabled: bool) {
+    let mut regs = Registers { ax: 0x3301, dx: enabled as u16, ..Registers::new() };
+    unsafe { bios::int::<0x21>(&mut regs) };
+}
+
+fn uninstall() {
+    without_interrupts(|| unsafe {
+        drop(core::ptr::replace(&raw mut GUARD, None));
+        EXIT_HOOK = None;
+    });
+}
+
+extern "C" fn on_break(_frame: &mut InterruptFrame) -> Action {
+    match unsafe { POLICY } {
+        Policy::Ignore => {}
+        Policy::Flag => PRESSED.store(true, Ordering::Relaxed),
+        Policy::Callback(callback) => callback(),
+    }
+
+    // Returning with `iret` makes DOS resume the interrupted call.
+    Action::Return
+}
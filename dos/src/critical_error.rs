@@ -0,0 +1,268 @@
+//! Answering critical errors (int 24h) without prompting "Abort, Retry, Fail?".
+//!
+//! **A failed write can look like it succeeded.** Failing a DOS call is only possible since
+//! DOS 3.1, and even then not for every error. Where it is not, [`Policy::Fail`] retries a
+//! write a few times and then ignores the error, which DOS reports to the caller as success
+//! even though the data never reached the device. Aborting is not an option, see [`Response`].
+
+use crate::drive::Drive;
+use crate::interrupt::{Action, InterruptFrame, InterruptGuard};
+use crate::process::{add_exit_hook, remove_exit_hook};
+use crate::{Error, FarPtr};
+use bios::cpu::without_interrupts;
+
+/// How a critical error is answered.
+#[derive(Debug, Clone, Copy)]
+pub enum Policy {
+    /// Every critical error fails the DOS call that caused it, which returns an [`Error`].
+    ///
+    /// Where DOS does not allow failing, a write is retried up to three times in a row and
+    /// then ignored, and anything else is ignored right away, or retried if ignoring is not
+    /// allowed either. See the [module docs](self) for what ignoring a write means.
+    Fail,
+    /// The function decides. It runs from within DOS, which only allows it to call functions
+    /// 01h to 0Ch, 30h and 59h.
+    Callback(fn(&CriticalError) -> Response),
+}
+
+/// The answer to a critical error.
+///
+/// Aborting is not offered, as DOS would terminate the program without running its exit hooks.
+#[repr(u8)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Response {
+    /// Pretends the operation succeeded.
+    Ignore = 0,
+    /// Tries the operation again.
+    Retry = 1,
+    /// Fails the DOS call, which returns an [`Error`].
+    Fail = 3,
+}
+
+/// Whether a critical error happened while reading or writing.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Operation {
+    Read,
+    Write,
+}
+
+/// The device a critical error happened on.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Device {
+    /// A disk, or the copy of its FAT in memory.
+    Drive(Drive),
+    /// A character device such as `PRN`, with its name padded with spaces.
+    Character([u8; 8]),
+    /// A disk DOS reports with a drive number beyond `Z:`.
+    Unknown,
+}
+
+/// A critical error (int 24h), as passed to a [`Policy::Callback`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct CriticalError {
+    device: Device,
+    error: Error,
+    /// AH on entry, with the operation and the allowed responses.
+    flags: u8,
+}
+
+impl CriticalError {
+    #[inline]
+    #[must_use]
+    pub const fn device(&self) -> Device {
+        self.device
+    }
+
+    #[inline]
+    #[must_use]
+    pub const fn error(&self) -> Error {
+        self.error
+    }
+
+    #[inline]
+    #[must_use]
+    pub const fn operation(&self) -> Operation {
+        if self.flags & 0x01 == 0 { Operation::Read } else { Operation::Write }
+    }
+
+    /// Returns whether DOS accepts `response`. It aborts the program on responses it does not
+    /// accept, which only happens with DOS versions before 3.1 or for errors outside a DOS call.
+    #[inline]
+    #[must_use]
+    pub const fn allows(&self, response: Response) -> bool {
+        let bit = match response {
+            Response::Fail => 0x08,
+            Response::Retry => 0x10,
+            Response::Ignore => 0x20,
+        };
+
+        self.flags & bit != 0
+    }
+
+    /// Returns the response [`Policy::Fail`] gives after `retries` retries in a row, see
+    /// [`Policy::Fail`]. DOS versions before 3.1 allow nothing explicitly but always take
+    /// retrying and ignoring.
+    fn fail_or_fallback(&self, retries: u8) -> Response {
+        let legacy = self.flags & 0x38 == 0;
+
+        if self.allows(Response::Fail) {
+            Response::Fail
+        } else if self.operation() == Operation::Write && retries < MAX_RETRIES && (legacy || self.allows(Response::Retry)) {
+            // Ignoring a write passes it off as done, so give a transient error a chance first.
+            Response::Retry
+        } else if legacy || self.allows(Response::Ignore) {
+            Response::Ignore
+        } else if self.allows(Response::Retry) {
+            Response::Retry
+        } else {
+            Response::Ignore
+        }
+    }
+}
+
+/// An error returned when the critical error handler cannot be installed.
+#[derive(thiserror::Error, Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum InstallError {
+    /// All exit hook slots are taken.
+    #[error("no free slot")]
+    NoFreeSlot,
+}
+
+/// The fixed part of a device driver header.
+#[repr(C, packed)]
+#[derive(Clone, Copy)]
+struct DeviceHeader {
+    next: u32,
+    attributes: u16,
+    strategy: u16,
+    interrupt: u16,
+    name: [u8; 8],
+}
+
+static mut POLICY: Policy = Policy::Fail;
+
+/// How many times in a row [`Policy::Fail`] retries a write before ignoring the error.
+const MAX_RETRIES: u8 = 3;
+
+/// The retries in a row [`Policy::Fail`] has answered with.
+static mut RETRIES: u8 = 0;
+
+/// The exit hook restoring the handler, or [`None`] if it is not installed.
+static mut EXIT_HOOK: Option<usize> = None;
+
+/// Keeps [`HANDLER`] installed, taken by [`restore`].
+static mut GUARD: Option<InterruptGuard> = None;
+
+crate::interrupt_handler! {
+    /// The int 24h handler, on a private stack so the callback has room.
+    static HANDLER = on_critical_error, stack = 512;
+}
+
+/// Answers critical errors according to `policy` (int 24h) instead of prompting
+/// "Abort, Retry, Fail?", replacing the policy if already installed.
+///
+/// The original handler is put back by [`restore`], or on exit through [`process::exit`] or by
+/// returning from `main`.
+///
+/// [`process::exit`]: crate::process::exit
+pub fn install(policy: Policy) -> Result<(), InstallError> {
+    without_interrupts(|| {
+        unsafe { POLICY = policy };
+
+        if unsafe { EXIT_HOOK }.is_some() {
+            return Ok(());
+        }
+
+        let exit_hook = add_exit_hook(uninstall).ok_or(InstallError::NoFreeSlot)?;
+
+        unsafe {
+            GUARD = Some(InterruptGuard::install(0x24, &HANDLER));
+            EXIT_HOOK = Some(exit_hook);
+        }
+
+        Ok(())
+    })
+}
+
+/// Puts back the handler that was installed before [`install`], so DOS prompts again.
+pub fn restore() {
+    if let Some(exit_hook) = unsafe { EXIT_HOOK } {
+        remove_exit_hook(exit_hook);
+        uninstall();
+    }
+}
+
+fn uninstall() {
+    without_interrupts(|| unsafe {
+        drop(core::ptr::replace(&raw mut GUARD, None));
+        EXIT_HOOK = None;
+    });
+}
+
+extern "C" fn on_critical_error(frame: &mut InterruptFrame) -> Action {
+    let flags = (frame.eax >> 8) as u8;
+    let header = unsafe { FarPtr::<DeviceHeader>::new(frame.ebp as u16, frame.esi as u16).read() };
+
+    // Disk errors have bit 7 clear, so do FAT errors, which come from block devices.
+    let device = if flags & 0x80 == 0 || header.attributes & 0x8000 == 0 {
+        // Panicking here would hang the machine inside DOS.
+        Drive::from_index(frame.eax as u8).map_or(Device::Unknown, Device::Drive)
+    } else {
+        Device::Character(header.name)
+    };
+
+    // The low byte of DI is the driver error code, which DOS reports as 13h higher.
+    let error = CriticalError { device, error: Error::from((frame.edi as u8).wrapping_add(0x13)), flags };
+
+    let response = match unsafe { POLICY } {
+        Policy::Fail => {
+            let response = error.fail_or_fallback(unsafe { RETRIES });
+            unsafe { RETRIES = if response == Response::Retry { RETRIES.saturating_add(1) } else { 0 } };
+            response
+        }
+        Policy::Callback(callback) => callback(&error),
+    };
+
+    frame.eax = (frame.eax & !0xFF) | response as u32;
+    Action::Return
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn error(flags: u8) -> CriticalError {
+        CriticalError { device: Device::Unknown, error: Error::GeneralFailure, flags }
+    }
+
+    #[test]
+    fn flags_decode() {
+        assert_eq!(error(0x00).operation(), Operation::Read);
+        assert_eq!(error(0x01).operation(), Operation::Write);
+        assert!(error(0x38).allows(Response::Fail));
+        assert!(error(0x38).allows(Response::Retry));
+        assert!(error(0x38).allows(Response::Ignore));
+        assert!(!error(0x30).allows(Response::Fail));
+    }
+
+    #[test]
+    fn fail_falls_back_to_an_allowed_response() {
+        assert_eq!(error(0x38).fail_or_fallback(0), Response::Fail);
+        assert_eq!(error(0x30).fail_or_fallback(0), Response::Ignore);
+        assert_eq!(error(0x10).fail_or_fallback(0), Response::Retry);
+        assert_eq!(error(0x00).fail_or_fallback(0), Response::Ignore);
+    }
+
+    #[test]
+    fn writes_are_retried_before_being_ignored() {
+        assert_eq!(error(0x39).fail_or_fallback(0), Response::Fail);
+        assert_eq!(error(0x31).fail_or_fallback(0), Response::Retry);
+        assert_eq!(error(0x31).fail_or_fallback(MAX_RETRIES - 1), Response::Retry);
+        assert_eq!(error(0x31).fail_or_fallback(MAX_RETRIES), Response::Ignore);
+        assert_eq!(error(0x21).fail_or_fallback(0), Response::Ignore);
+
+        // Before DOS 3.1, retrying is always allowed.
+        assert_eq!(error(0x01).fail_or_fallback(0), Response::Retry);
+        assert_eq!(error(0x01).fail_or_fallback(MAX_RETRIES), Response::Ignore);
+    }
+}
@@ -0,0 +1,230 @@
+use crate::{Error, int21};
+use bios::Registers;
+
+pub type Result<T> = core::result::Result<T, Error>;
+
+/// A logical drive, `A:` being drive index 0.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[repr(transparent)]
+pub struct Drive(u8);
+
+impl Drive {
+    /// Returns the drive for the letter `A` to `Z`, case-insensitively.
+    #[inline]
+    pub const fn from_letter(letter: u8) -> Option<Self> {
+        match letter.to_ascii_uppercase() {
+            letter @ b'A'..=b'Z' => Some(Self(letter - b'A')),
+            _ => None,
+        }
+    }
+
+    /// Returns the drive for the index `0` (`A:`) to `25` (`Z:`).
+    #[inline]
+    pub const fn from_index(index: u8) -> Option<Self> {
+        if index < 26 { Some(Self(index)) } else { None }
+    }
+
+    /// Returns the drive letter in uppercase.
+    #[inline]
+    pub const fn letter(self) -> u8 {
+        b'A' + self.0
+    }
+
+    /// Returns the zero-based drive index.
+    #[inline]
+    pub const fn index(self) -> u8 {
+        self.0
+    }
+
+    /// Returns `true` if DOS knows this drive (int 21h, AX=4409h).
+    ///
+    /// Drives that exist but have no media inserted are still reported as existing.
+    #[inline]
+    #[must_use]
+    pub fn exists(self) -> bool {
+        self.ioctl_attributes().is_ok()
+    }
+
+    /// Returns the size and free space of the volume in this drive.
+    ///
+    /// The FAT32 aware call (int 21h, AX=7303h) is tried first. Older versions of DOS only
+    /// have int 21h, AH=36h, which caps both values just below 2 GiB.
+    pub fn space(self) -> Result<Space> {
+        self.extended_space().or_else(|_| self.legacy_space())
+    }
+
+    fn extended_space(self) -> Result<Space> {
+        let root = [self.letter(), b':', b'\\', 0];
+        let mut info = ExtendedFreeSpace::default();
+
+        let regs = unsafe {
+            int21(Registers {
+                ax: 0x7303,
+                cx: size_of::<ExtendedFreeSpace>() as u16,
+                dx: root.as_ptr() as u16,
+                di: &raw mut info as u16,
+                ..Registers::new()
+            })
+        }?;
+
+        // DOS versions without the call leave AL cleared and may not set the carry flag.
+        if regs.ax == 0x7300 {
+            return Err(Error::InvalidFunction);
+        }
+
+        Ok(Space {
+            cluster_size: info.sectors_per_cluster * info.bytes_per_sector,
+            free_clusters: info.available_clusters,
+            total_clusters: info.total_clusters,
+        })
+    }
+
+    fn legacy_space(self) -> Result<Space> {
+        let regs = unsafe { int21(Registers { ax: 0x3600, dx: Self::number(Some(self)) as u16, ..Registers::new() }) }?;
+
+        if regs.ax == 0xFFFF {
+            return Err(Error::InvalidDrive);
+        }
+
+        Ok(Space { cluster_size: regs.ax as u32 * regs.cx as u32, free_clusters: regs.bx as u32, total_clusters: regs.dx as u32 })
+    }
+
+    /// Classifies this drive (int 21h, AX=4409h and AX=4408h).
+    pub fn drive_type(self) -> Result<DriveType> {
+        let attributes = self.ioctl_attributes()?;
+
+        if attributes & (1 << 15) != 0 {
+            return Ok(DriveType::Substituted);
+        }
+
+        if attributes & (1 << 12) != 0 {
+            return Ok(DriveType::Remote);
+        }
+
+        let regs = unsafe { int21(Registers { ax: 0x4408, bx: Self::number(Some(self)) as u16, ..Registers::new() }) }?;
+
+        match regs.ax {
+            0 => Ok(DriveType::Removable),
+            _ => Ok(DriveType::Fixed),
+        }
+    }
+
+    /// Returns the device attribute word of the block device behind this drive (int 21h, AX=4409h).
+    fn ioctl_attributes(self) -> Result<u16> {
+        let regs = unsafe { int21(Registers { ax: 0x4409, bx: Self::number(Some(self)) as u16, ..Registers::new() }) }?;
+        Ok(regs.dx)
+    }
+
+    /// Returns the drive number as used by DOS functions that accept the default drive,
+    /// where `0` is the default drive and `1` is `A:`.
+    #[inline]
+    pub(crate) const fn number(drive: Option<Self>) -> u8 {
+        match drive {
+            Some(Self(index)) => index + 1,
+            None => 0,
+        }
+    }
+}
+
+/// The kind of device behind a [`Drive`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum DriveType {
+    /// A drive with removable media, such as a floppy disk.
+    Removable,
+    /// A local drive with fixed media.
+    Fixed,
+    /// A drive redirected over the network, or by a redirector such as MSCDEX.
+    Remote,
+    /// A drive aliasing a directory on another drive, created with `SUBST`.
+    Substituted,
+}
+
+/// The size and free space of a volume, see [`Drive::space`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Space {
+    /// Bytes per cluster, the unit of allocation.
+    pub cluster_size: u32,
+    pub free_clusters: u32,
+    pub total_clusters: u32,
+}
+
+impl Space {
+    /// Returns the free space, in bytes.
+    #[inline]
+    #[must_use]
+    pub const fn free(&self) -> u64 {
+        self.cluster_size as u64 * self.free_clusters as u64
+    }
+
+    /// Returns the size of the volume, in bytes.
+    #[inline]
+    #[must_use]
+    pub const fn total(&self) -> u64 {
+        self.cluster_size as u64 * self.total_clusters as u64
+    }
+}
+
+/// The buffer filled in by int 21h, AX=7303h.
+#[derive(Default)]
+#[repr(C)]
+struct ExtendedFreeSpace {
+    size: u16,
+    /// Must be zero on input.
+    version: u16,
+    sectors_per_cluster: u32,
+    bytes_per_sector: u32,
+    available_clusters: u32,
+    total_clusters: u32,
+    available_sectors: u32,
+    total_sectors: u32,
+    available_units: u32,
+    total_units: u32,
+    reserved: [u8; 8],
+}
+
+/// An iterator over the logical drives DOS knows, see [`drives`].
+#[derive(Debug, Clone)]
+pub struct Drives {
+    next: u8,
+}
+
+impl Iterator for Drives {
+    type Item = Drive;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        while let Some(drive) = Drive::from_index(self.next) {
+            self.next += 1;
+
+            if drive.exists() {
+                return Some(drive);
+            }
+        }
+
+        None
+    }
+}
+
+/// Returns an iterator over the valid logical drives, from `A:` to `Z:`.
+#[inline]
+#[must_use]
+pub fn drives() -> Drives {
+    Drives { next: 0 }
+}
+
+/// Returns the current default drive (int 21h, AH=19h).
+#[inline]
+pub fn current() -> Drive {
+    let mut regs = Registers { ax: 0x1900, ..Registers::new() };
+    unsafe { bios::int::<0x21>(&mut regs) };
+    Drive(regs.al())
+}
+
+/// Makes `drive` the current default drive (int 21h, AH=0Eh).
+///
+/// DOS does not report failure for this call, so the drive is read back to detect drives
+/// that do not exist.
+pub fn set_current(drive: Drive) -> Result<()> {
+    unsafe { int21(Registers { ax: 0x0E00, dx: drive.0 as u16, ..Registers::new() }) }?;
+
+    if current() == drive { Ok(()) } else { Err(Error::InvalidDrive) }
+}
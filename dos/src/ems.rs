@@ -0,0 +1,312 @@
+use crate::FarPtr;
+use crate::error::error_codes;
+use bios::Registers;
+
+pub type Result<T> = core::result::Result<T, EmsError>;
+
+/// The size of a page, logical or physical.
+pub const PAGE_SIZE: usize = 16 * 1024;
+
+/// The physical pages in the page frame.
+pub const FRAME_PAGES: u8 = 4;
+
+error_codes! {
+    /// An error code returned by the expanded memory manager.
+    pub enum EmsError {
+        InternalError = 0x80 => "Internal error in the expanded memory manager.",
+        HardwareMalfunction = 0x81 => "Expanded memory hardware malfunction.",
+        Busy = 0x82 => "Memory manager busy.",
+        InvalidHandle = 0x83 => "Invalid handle.",
+        UndefinedFunction = 0x84 => "Function not defined, or no expanded memory manager installed.",
+        OutOfHandles = 0x85 => "No more handles available.",
+        ContextError = 0x86 => "Error in saving or restoring the mapping context.",
+        NotEnoughPages = 0x87 => "More pages requested than exist.",
+        NotEnoughFreePages = 0x88 => "More pages requested than are free.",
+        ZeroPages = 0x89 => "Zero pages requested.",
+        LogicalPageOutOfRange = 0x8A => "Logical page out of range for the handle.",
+        PhysicalPageOutOfRange = 0x8B => "Physical page out of range.",
+        SaveAreaFull = 0x8C => "Mapping context save area full.",
+        ContextAlreadySaved = 0x8D => "Mapping context already saved for the handle.",
+        ContextNotSaved = 0x8E => "No mapping context saved for the handle.",
+        UndefinedSubfunction = 0x8F => "Subfunction not defined.",
+    }
+}
+
+/// The version of the LIM specification a manager implements, see [`version`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Version {
+    pub major: u8,
+    pub minor: u8,
+}
+
+/// Whether a manager is installed, or [`None`] if not looked up yet.
+static mut INSTALLED: Option<bool> = None;
+
+/// The segment of the page frame, or `0` if not looked up yet.
+static mut FRAME: u16 = 0;
+
+/// Counts of 16 KiB pages, see [`pages`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Pages {
+    pub free: u16,
+    pub total: u16,
+}
+
+/// Calls the expanded memory manager (int 67h) with `regs`, mapping a nonzero status in AH to
+/// an [`EmsError`].
+///
+/// # Safety
+///
+/// See [`bios::int`].
+#[inline]
+unsafe fn int67(mut regs: Registers) -> Result<Registers> {
+    if !is_installed() {
+        return Err(EmsError::UndefinedFunction);
+    }
+
+    unsafe { bios::int::<0x67>(&mut regs) };
+
+    if regs.ah() == 0 { Ok(regs) } else { Err(EmsError::from(regs.ah())) }
+}
+
+/// Returns `true` if an expanded memory manager such as EMM386 is installed, by looking for
+/// its device name `EMMXXXX0` in the segment its int 67h handler lives in.
+///
+/// The vector is read straight from the interrupt vector table rather than through DOS, and
+/// the answer is kept, so this is safe to call from interrupt handlers.
+#[must_use]
+pub fn is_installed() -> bool {
+    if let Some(installed) = unsafe { INSTALLED } {
+        return installed;
+    }
+
+    let [_, segment] = unsafe { FarPtr::<[u16; 2]>::new(0, 0x67 * 4).read() };
+    let installed = segment != 0 && unsafe { FarPtr::<[u8; 8]>::new(segment, 0x0A).read() } == *b"EMMXXXX0";
+
+    unsafe { INSTALLED = Some(installed) };
+    installed
+}
+
+/// Returns the version of the LIM specification the manager implements (int 67h, AH=46h).
+pub fn version() -> Result<Version> {
+    let regs = unsafe { int67(Registers { ax: 0x4600, ..Registers::new() }) }?;
+    let version = bios::rtc::from_bcd(regs.al()).ok_or(EmsError::InternalError)?;
+    Ok(Version { major: version / 10, minor: version % 10 })
+}
+
+/// Returns the segment of the page frame, the window through which mapped pages are seen
+/// (int 67h, AH=41h).
+pub fn page_frame() -> Result<u16> {
+    if unsafe { FRAME } == 0 {
+        let regs = unsafe { int67(Registers { ax: 0x4100, ..Registers::new() }) }?;
+        unsafe { FRAME = regs.bx };
+    }
+
+    Ok(unsafe { FRAME })
+}
+
+/// Returns the free and total pages of expanded memory (int 67h, AH=42h).
+pub fn pages() -> Result<Pages> {
+    unsafe { int67(Registers { ax: 0x4200, ..Registers::new() }) }.map(|regs| Pages { free: regs.bx, total: regs.dx })
+}
+
+/// A handle to allocated expanded memory, deallocated when dropped.
+#[derive(Debug)]
+pub struct EmsHandle {
+    handle: u16,
+    pages: u16,
+    /// Whether a mapping is saved under the handle, see [`EmsHandle::save_map`].
+    saved: bool,
+}
+
+impl EmsHandle {
+    /// Allocates `pages` logical pages of 16 KiB (int 67h, AH=43h).
+    pub fn new(pages: u16) -> Result<Self> {
+        let regs = unsafe { int67(Registers { ax: 0x4300, bx: pages, ..Registers::new() }) }?;
+        Ok(Self { handle: regs.dx, pages, saved: false })
+    }
+
+    /// Returns the raw handle.
+    #[inline]
+    #[must_use]
+    pub const fn handle(&self) -> u16 {
+        self.handle
+    }
+
+    /// Returns the number of logical pages.
+    #[inline]
+    #[must_use]
+    pub const fn pages(&self) -> u16 {
+        self.pages
+    }
+
+    /// Changes the number of logical pages, keeping the contents of the ones that remain
+    /// (int 67h, AH=51h, LIM 4.0).
+    pub fn resize(&mut self, pages: u16) -> Result<()> {
+        unsafe { int67(Registers { ax: 0x5100, bx: pages, dx: self.handle, ..Registers::new() }) }?;
+        self.pages = pages;
+        Ok(())
+    }
+
+    /// Maps logical page `logical` into physical page `physical` of the page frame
+    /// (int 67h, AH=44h).
+    pub fn map(&mut self, physical: u8, logical: u16) -> Result<()> {
+        unsafe { int67(Registers { ax: 0x4400 | physical as u16, bx: logical, dx: self.handle, ..Registers::new() }) }.map(|_| ())
+    }
+
+    /// Unmaps physical page `physical` of the page frame (int 67h, AH=44h, LIM 4.0).
+    pub fn unmap(&mut self, physical: u8) -> Result<()> {
+        self.map(physical, 0xFFFF)
+    }
+
+    /// Saves the mapping of the page frame with the manager, under this handle
+    /// (int 67h, AH=47h). Only one mapping can be saved per handle.
+    pub fn save_map(&mut self) -> Result<()> {
+        unsafe { int67(Registers { ax: 0x4700, dx: self.handle, ..Registers::new() }) }?;
+        self.saved = true;
+        Ok(())
+    }
+
+    /// Restores the mapping saved by [`save_map`] (int 67h, AH=48h).
+    ///
+    /// [`save_map`]: EmsHandle::save_map
+    pub fn restore_map(&mut self) -> Result<()> {
+        unsafe { int67(Registers { ax: 0x4800, dx: self.handle, ..Registers::new() }) }?;
+        self.saved = false;
+        Ok(())
+    }
+
+    /// Copies the bytes at `offset` into `buf`, mapping the pages through physical page 0.
+    ///
+    /// Physical page 0 is left mapped to the last page copied. Save the mapping around this,
+    /// with [`save_map`] or a [`MapContext`], where something else relies on it.
+    ///
+    /// [`save_map`]: EmsHandle::save_map
+    pub fn read(&mut self, offset: u32, buf: &mut [u8]) -> Result<()> {
+        let frame = self.check(offset, buf.len())?;
+
+        for (page, start, chunk) in chunks(offset, buf.len()) {
+            self.map(0, page)?;
+            unsafe { FarPtr::new(frame, start).copy_to_slice(&mut buf[chunk]) };
+        }
+
+        Ok(())
+    }
+
+    /// Copies `buf` to the bytes at `offset`, mapping the pages through physical page 0.
+    ///
+    /// Physical page 0 is left mapped to the last page copied, as with [`read`].
+    ///
+    /// [`read`]: EmsHandle::read
+    pub fn write(&mut self, offset: u32, buf: &[u8]) -> Result<()> {
+        let frame = self.check(offset, buf.len())?;
+
+        for (page, start, chunk) in chunks(offset, buf.len()) {
+            self.map(0, page)?;
+            unsafe { FarPtr::new(frame, start).copy_from_slice(&buf[chunk]) };
+        }
+
+        Ok(())
+    }
+
+    /// Returns the segment of the page frame if `length` bytes at `offset` lie within the
+    /// logical pages.
+    fn check(&self, offset: u32, length: usize) -> Result<u16> {
+        match (offset as usize).checked_add(length) {
+            Some(end) if end <= self.pages as usize * PAGE_SIZE => page_frame(),
+            _ => Err(EmsError::LogicalPageOutOfRange),
+        }
+    }
+}
+
+impl Drop for EmsHandle {
+    fn drop(&mut self) {
+        // Function 45h fails while a mapping is saved under the handle, restoring it clears that.
+        if self.saved {
+            let _ = self.restore_map();
+        }
+
+        let _ = unsafe { int67(Registers { ax: 0x4500, dx: self.handle, ..Registers::new() }) };
+    }
+}
+
+/// Splits `length` bytes at `offset` at page boundaries, yielding the logical page, the
+/// offset within it and the range of the bytes.
+fn chunks(offset: u32, length: usize) -> impl Iterator<Item = (u16, u16, core::ops::Range<usize>)> {
+    let mut done = 0;
+
+    core::iter::from_fn(move || {
+        if done == length {
+            return None;
+        }
+
+        let position = offset as usize + done;
+        let start = position % PAGE_SIZE;
+        let len = (PAGE_SIZE - start).min(length - done);
+        let chunk = done..done + len;
+        done += len;
+
+        Some(((position / PAGE_SIZE) as u16, start as u16, chunk))
+    })
+}
+
+/// A snapshot of the mapping of the page frame, taken and restored without going through a
+/// handle (int 67h, AH=4Eh, LIM 4.0). Interrupt handlers use this to leave the mapping of
+/// the code they interrupt as they found it.
+#[derive(Clone, Copy)]
+pub struct MapContext {
+    buf: [u8; 255],
+    len: u8,
+}
+
+impl MapContext {
+    /// Saves the current mapping (int 67h, AX=4E00h).
+    pub fn save() -> Result<Self> {
+        let size = unsafe { int67(Registers { ax: 0x4E03, ..Registers::new() }) }?.al();
+        let mut context = Self { buf: [0; 255], len: size };
+
+        unsafe { int67(Registers { ax: 0x4E00, di: context.buf.as_mut_ptr() as u16, ..Registers::new() }) }?;
+        Ok(context)
+    }
+
+    /// Restores the mapping (int 67h, AX=4E01h).
+    pub fn restore(&self) -> Result<()> {
+        unsafe { int67(Registers { ax: 0x4E01, si: self.buf.as_ptr() as u16, ..Registers::new() }) }.map(|_| ())
+    }
+}
+
+impl core::fmt::Debug for MapContext {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        f.debug_struct("MapContext").field("len", &self.len).finish_non_exhaustive()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use core::ops::Range;
+
+    fn collect(offset: u32, length: usize) -> [Option<(u16, u16, Range<usize>)>; 4] {
+        let mut chunks = chunks(offset, length);
+        core::array::from_fn(|_| chunks.next())
+    }
+
+    #[test]
+    fn chunks_within_a_page() {
+        assert_eq!(collect(0, 0), [None, None, None, None]);
+        assert_eq!(collect(100, 50), [Some((0, 100, 0..50)), None, None, None]);
+        assert_eq!(collect(PAGE_SIZE as u32, PAGE_SIZE), [Some((1, 0, 0..PAGE_SIZE)), None, None, None]);
+    }
+
+    #[test]
+    fn chunks_split_at_page_boundaries() {
+        let last = PAGE_SIZE as u16 - 1;
+        assert_eq!(collect(PAGE_SIZE as u32 - 1, 2), [Some((0, last, 0..1)), Some((1, 0, 1..2)), None, None]);
+
+        let length = 2 * PAGE_SIZE + 2;
+        assert_eq!(
+            collect(PAGE_SIZE as u32 - 1, length),
+            [Some((0, last, 0..1)), Some((1, 0, 1..PAGE_SIZE + 1)), Some((2, 0, PAGE_SIZE + 1..length - 1)), Some((3, 0, length - 1..length))]
+        );
+    }
+}
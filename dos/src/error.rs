@@ -0,0 +1,227 @@
+use crate::int21;
+use bios::Registers;
+use core::fmt::{self, Display};
+
+/// Declares an enum over a table of documented codes, with an `Unknown` fallback so that
+/// converting from the raw code is total.
+macro_rules! error_codes {
+    (
+        $(#[$attr:meta])*
+        $vis:vis enum $name:ident {
+            $($variant:ident = $code:literal => $message:literal,)*
+        }
+    ) => {
+        $(#[$attr])*
+        #[derive(thiserror::Error, Debug, Clone, Copy, PartialEq, Eq, Hash)]
+        $vis enum $name {
+            $(
+                #[doc = $message]
+                #[error($message)]
+                $variant,
+            )*
+            /// A code that is not documented.
+            #[error("Unknown ({0:#04X}).")]
+            Unknown(u8),
+        }
+
+        impl $name {
+            /// Returns the raw code.
+            #[inline]
+            #[must_use]
+            pub const fn code(&self) -> u8 {
+                match *self {
+                    $(Self::$variant => $code,)*
+                    Self::Unknown(code) => code,
+                }
+            }
+        }
+
+        impl From<u8> for $name {
+            #[inline]
+            fn from(code: u8) -> Self {
+                match code {
+                    $($code => Self::$variant,)*
+                    code => Self::Unknown(code),
+                }
+            }
+        }
+    };
+}
+
+pub(crate) use error_codes;
+
+error_codes! {
+    /// An error code returned by a DOS function.
+    pub enum Error {
+        InvalidFunction = 0x01 => "Invalid function.",
+        FileNotFound = 0x02 => "File not found.",
+        PathNotFound = 0x03 => "Path not found.",
+        TooManyOpenFiles = 0x04 => "Too many open files.",
+        AccessDenied = 0x05 => "Access denied.",
+        InvalidHandle = 0x06 => "Invalid handle.",
+        MemoryControlBlocksDestroyed = 0x07 => "Memory control blocks destroyed.",
+        InsufficientMemory = 0x08 => "Insufficient memory.",
+        InvalidMemoryBlock = 0x09 => "Invalid memory block address.",
+        InvalidEnvironment = 0x0A => "Invalid environment.",
+        InvalidFormat = 0x0B => "Invalid format.",
+        InvalidAccessCode = 0x0C => "Invalid access code.",
+        InvalidData = 0x0D => "Invalid data.",
+        InvalidDrive = 0x0F => "Invalid drive.",
+        RemoveCurrentDirectory = 0x10 => "Attempted to remove the current directory.",
+        NotSameDevice = 0x11 => "Not same device.",
+        NoMoreFiles = 0x12 => "No more files.",
+        WriteProtected = 0x13 => "Disk is write-protected.",
+        UnknownUnit = 0x14 => "Unknown unit.",
+        DriveNotReady = 0x15 => "Drive not ready.",
+        UnknownCommand = 0x16 => "Unknown command.",
+        DataError = 0x17 => "Data error (CRC).",
+        BadRequestLength = 0x18 => "Bad request structure length.",
+        SeekError = 0x19 => "Seek error.",
+        UnknownMedia = 0x1A => "Unknown media type.",
+        SectorNotFound = 0x1B => "Sector not found.",
+        PrinterOutOfPaper = 0x1C => "Printer out of paper.",
+        WriteFault = 0x1D => "Write fault.",
+        ReadFault = 0x1E => "Read fault.",
+        GeneralFailure = 0x1F => "General failure.",
+        SharingViolation = 0x20 => "Sharing violation.",
+        LockViolation = 0x21 => "Lock violation.",
+        DiskChangeInvalid = 0x22 => "Disk change invalid.",
+        FcbUnavailable = 0x23 => "FCB unavailable.",
+        InvalidSharingBuffer = 0x24 => "Invalid sharing buffer.",
+        CodePageMismatch = 0x25 => "Code page mismatch.",
+        EndOfInput = 0x26 => "Cannot complete file operation (out of input).",
+        DiskFull = 0x27 => "Insufficient disk space.",
+        NetworkNotSupported = 0x32 => "Network request not supported.",
+        RemoteNotListening = 0x33 => "Remote computer not listening.",
+        DuplicateName = 0x34 => "Duplicate name on network.",
+        BadNetworkPath = 0x35 => "Network path not found.",
+        NetworkBusy = 0x36 => "Network busy.",
+        NetworkDeviceGone = 0x37 => "Network device no longer exists.",
+        TooManyCommands = 0x38 => "Network BIOS command limit exceeded.",
+        AdapterHardwareError = 0x39 => "Network adapter hardware error.",
+        BadNetworkResponse = 0x3A => "Incorrect response from network.",
+        UnexpectedNetworkError = 0x3B => "Unexpected network error.",
+        BadRemoteAdapter = 0x3C => "Incompatible remote adapter.",
+        PrintQueueFull = 0x3D => "Print queue full.",
+        NoSpoolSpace = 0x3E => "Not enough space for print file.",
+        NoPrintSpace = 0x3F => "Not enough space to print file.",
+        NetworkNameDeleted = 0x40 => "Network name was deleted.",
+        NetworkAccessDenied = 0x41 => "Network access denied.",
+        BadDeviceType = 0x42 => "Network device type incorrect.",
+        BadNetworkName = 0x43 => "Network name not found.",
+        TooManyNames = 0x44 => "Network name limit exceeded.",
+        TooManySessions = 0x45 => "Network BIOS session limit exceeded.",
+        SharingPaused = 0x46 => "Temporarily paused.",
+        RequestNotAccepted = 0x47 => "Network request not accepted.",
+        RedirectionPaused = 0x48 => "Network print/disk redirection paused.",
+        NetworkNotInstalled = 0x49 => "Network software not installed.",
+        UnexpectedAdapterClose = 0x4A => "Unexpected adapter close.",
+        PasswordExpired = 0x4B => "Password expired.",
+        InvalidLoginTime = 0x4C => "Login attempt invalid at this time.",
+        NetworkDiskFull = 0x4D => "Disk limit exceeded on network node.",
+        NotLoggedIn = 0x4E => "Not logged in to network node.",
+        FileExists = 0x50 => "File exists.",
+        CannotMake = 0x52 => "Cannot make directory entry.",
+        FailOnInt24 = 0x53 => "Fail on int 24h.",
+        TooManyRedirections = 0x54 => "Too many redirections.",
+        DuplicateRedirection = 0x55 => "Duplicate redirection.",
+        InvalidPassword = 0x56 => "Invalid password.",
+        InvalidParameter = 0x57 => "Invalid parameter.",
+        NetworkWriteFault = 0x58 => "Network write fault.",
+        NetworkFunctionNotSupported = 0x59 => "Function not supported on network.",
+        ComponentNotInstalled = 0x5A => "Required system component not installed.",
+    }
+}
+
+error_codes! {
+    /// The class of an [`ExtendedError`], describing what kind of failure occurred.
+    pub enum ErrorClass {
+        OutOfResources = 0x01 => "Out of resources.",
+        Temporary = 0x02 => "Temporary situation.",
+        Authorization = 0x03 => "Authorization denied.",
+        Internal = 0x04 => "Internal error.",
+        HardwareFailure = 0x05 => "Hardware failure.",
+        SystemFailure = 0x06 => "System failure.",
+        ApplicationError = 0x07 => "Application program error.",
+        NotFound = 0x08 => "Not found.",
+        BadFormat = 0x09 => "Bad format.",
+        Locked = 0x0A => "Locked.",
+        MediaError = 0x0B => "Media error.",
+        AlreadyExists = 0x0C => "Already exists.",
+        Unspecified = 0x0D => "Unspecified.",
+    }
+}
+
+error_codes! {
+    /// The action DOS suggests in response to an [`ExtendedError`].
+    pub enum ErrorAction {
+        Retry = 0x01 => "Retry.",
+        DelayedRetry = 0x02 => "Retry after a delay.",
+        Reenter = 0x03 => "Prompt the user to reenter input.",
+        AbortAfterCleanup = 0x04 => "Abort after cleanup.",
+        Abort = 0x05 => "Abort immediately.",
+        Ignore = 0x06 => "Ignore.",
+        RetryAfterIntervention = 0x07 => "Retry after user intervention.",
+    }
+}
+
+error_codes! {
+    /// Where the failure behind an [`ExtendedError`] occurred.
+    pub enum ErrorLocus {
+        Unspecified = 0x01 => "Unspecified.",
+        BlockDevice = 0x02 => "Block device.",
+        Network = 0x03 => "Network.",
+        SerialDevice = 0x04 => "Serial device.",
+        Memory = 0x05 => "Memory.",
+    }
+}
+
+/// Extended information about the most recent failed DOS call (int 21h, AH=59h).
+///
+/// Unlike the bare [`Error`] code, the [`action`] tells whether a failure is worth retrying.
+///
+/// [`action`]: ExtendedError::action
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct ExtendedError {
+    pub error: Error,
+    pub class: ErrorClass,
+    pub action: ErrorAction,
+    pub locus: ErrorLocus,
+}
+
+impl ExtendedError {
+    /// Returns the extended information about the most recent failed DOS call, or [`None`]
+    /// if it succeeded.
+    ///
+    /// Must be called before any other DOS call, which would overwrite the information.
+    #[must_use]
+    pub fn last() -> Option<Self> {
+        let regs = unsafe { int21(Registers { ax: 0x5900, bx: 0x0000, ..Registers::new() }) }.ok()?;
+
+        match regs.ax {
+            0 => None,
+            code => Some(Self {
+                error: Error::from(code as u8),
+                class: ErrorClass::from(regs.bh()),
+                action: ErrorAction::from(regs.bl()),
+                locus: ErrorLocus::from(regs.ch()),
+            }),
+        }
+    }
+
+    /// Returns `true` if DOS suggests retrying the operation, possibly after a delay or
+    /// after the user has intervened.
+    #[inline]
+    #[must_use]
+    pub const fn is_retryable(&self) -> bool {
+        matches!(self.action, ErrorAction::Retry | ErrorAction::DelayedRetry | ErrorAction::RetryAfterIntervention)
+    }
+}
+
+impl Display for ExtendedError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} Class: {} Locus: {} Action: {}", self.error, self.class, self.locus, self.action)
+    }
+}
+
+impl core::error::Error for ExtendedError {}
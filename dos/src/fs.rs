@@ -1,49 +1,404 @@
-use crate::Error;
-use core::arch::asm;
-use core::ops::{BitOr, BitOrAssign};
+use crate::drive::Drive;
+use crate::time::FileTime;
+use crate::{Error, int21};
+use bios::Registers;
+use core::ops::{BitAnd, BitAndAssign, BitOr, BitOrAssign, Not};
 
 pub type Result<T> = core::result::Result<T, Error>;
 
+/// Access mode requested when opening a [`File`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[repr(u8)]
+pub enum Access {
+    /// Read-only.
+    Read = 0x00,
+    /// Write-only.
+    Write = 0x01,
+    /// Read-write.
+    ReadWrite = 0x02,
+}
+
+/// Enumeration of possible methods to seek within a [`File`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SeekFrom {
+    /// Sets the offset to the provided number of bytes.
+    Start(u32),
+    /// Sets the offset to the size of the file plus the specified number of bytes.
+    End(i32),
+    /// Sets the offset to the current position plus the specified number of bytes.
+    Current(i32),
+}
+
+/// An open DOS file handle.
+///
+/// The handle is closed when the value is dropped. Use [`File::close`] to observe errors on close.
 #[derive(Debug)]
 #[repr(transparent)]
 pub struct File(u16);
 
 impl File {
-    pub fn open(path: *const u8) -> Result<Self> {
-        let Self(handle);
-        unsafe { asm!("int 0x21", in("ah") 0x0Fu8, in("dx") path, lateout("ax") handle, options(nostack)) }
-
-        if !cf() {
-            Ok(Self(handle))
-        } else {
-            // We assume the DOS implementation is correct.
-            Err(unsafe { Error::new_unchecked((handle & 0xFF) as u8) })
+    /// Opens an existing file with the given access mode (int 21h, AH=3Dh).
+    pub fn open(path: *const u8, access: Access) -> Result<Self> {
+        let regs = unsafe { int21(Registers { ax: 0x3D00 | access as u16, dx: path as u16, ..Registers::new() }) }?;
+        Ok(Self(regs.ax))
+    }
+
+    /// Creates a file, truncating it if it already exists, and opens it in read-write mode (int 21h, AH=3Ch).
+    pub fn create(path: *const u8, flags: Flags) -> Result<Self> {
+        let regs = unsafe { int21(Registers { ax: 0x3C00, cx: flags.0 as u16, dx: path as u16, ..Registers::new() }) }?;
+        Ok(Self(regs.ax))
+    }
+
+    /// Creates a new file and opens it in read-write mode, failing if it already exists (int 21h, AH=5Bh).
+    pub fn create_new(path: *const u8, flags: Flags) -> Result<Self> {
+        let regs = unsafe { int21(Registers { ax: 0x5B00, cx: flags.0 as u16, dx: path as u16, ..Registers::new() }) }?;
+        Ok(Self(regs.ax))
+    }
+
+    /// Creates a [`File`] from a raw DOS handle.
+    ///
+    /// # Safety
+    ///
+    /// `handle` must be an open handle that is not owned by anything else, as it is closed on drop.
+    #[inline]
+    pub const unsafe fn from_handle(handle: u16) -> Self {
+        Self(handle)
+    }
+
+    /// Returns the raw DOS handle.
+    #[inline]
+    pub const fn handle(&self) -> u16 {
+        self.0
+    }
+
+    /// Reads up to `buf.len()` bytes from the file (int 21h, AH=3Fh), returning how many bytes were read.
+    ///
+    /// A single call transfers at most 65535 bytes. A return value of `0` means end of file.
+    pub fn read(&self, buf: &mut [u8]) -> Result<usize> {
+        let len = buf.len().min(u16::MAX as usize) as u16;
+        let regs = unsafe { int21(Registers { ax: 0x3F00, bx: self.0, cx: len, dx: buf.as_mut_ptr() as u16, ..Registers::new() }) }?;
+        Ok(regs.ax.into())
+    }
+
+    /// Writes up to `buf.len()` bytes to the file (int 21h, AH=40h), returning how many bytes were written.
+    ///
+    /// A single call transfers at most 65535 bytes. Writing an empty buffer does nothing, as DOS would
+    /// otherwise interpret it as a request to truncate the file, see [`File::truncate`].
+    pub fn write(&self, buf: &[u8]) -> Result<usize> {
+        if buf.is_empty() {
+            return Ok(0);
         }
+
+        let len = buf.len().min(u16::MAX as usize) as u16;
+        let regs = unsafe { int21(Registers { ax: 0x4000, bx: self.0, cx: len, dx: buf.as_ptr() as u16, ..Registers::new() }) }?;
+        Ok(regs.ax.into())
+    }
+
+    /// Truncates or extends the file to the current position (int 21h, AH=40h, CX=0).
+    pub fn truncate(&self) -> Result<()> {
+        unsafe { int21(Registers { ax: 0x4000, bx: self.0, cx: 0, ..Registers::new() }) }?;
+        Ok(())
+    }
+
+    /// Moves the file pointer (int 21h, AH=42h), returning the new position from the start of the file.
+    pub fn seek(&self, pos: SeekFrom) -> Result<u32> {
+        let (origin, offset) = match pos {
+            SeekFrom::Start(offset) => (0x00, offset),
+            SeekFrom::Current(offset) => (0x01, offset as u32),
+            SeekFrom::End(offset) => (0x02, offset as u32),
+        };
+
+        let regs = unsafe { int21(Registers { ax: 0x4200 | origin, bx: self.0, cx: (offset >> 16) as u16, dx: offset as u16, ..Registers::new() }) }?;
+        Ok((regs.dx as u32) << 16 | regs.ax as u32)
+    }
+
+    /// Returns the last-write timestamp (int 21h, AX=5700h).
+    pub fn modified(&self) -> Result<FileTime> {
+        let regs = unsafe { int21(Registers { ax: 0x5700, bx: self.0, ..Registers::new() }) }?;
+        Ok(FileTime::from_raw(regs.dx, regs.cx))
+    }
+
+    /// Sets the last-write timestamp (int 21h, AX=5701h).
+    ///
+    /// DOS stamps the file again when it is closed after a write, so set this after the last write.
+    pub fn set_modified(&self, time: FileTime) -> Result<()> {
+        unsafe { int21(Registers { ax: 0x5701, bx: self.0, cx: time.time(), dx: time.date(), ..Registers::new() }) }?;
+        Ok(())
+    }
+
+    /// Returns the last-access date (int 21h, AX=5704h). DOS only records the day, so the time is midnight.
+    ///
+    /// Only DOS 7 and later support this, others fail with [`Error::InvalidFunction`].
+    pub fn accessed(&self) -> Result<FileTime> {
+        let regs = unsafe { int21(Registers { ax: 0x5704, bx: self.0, ..Registers::new() }) }?;
+        Ok(FileTime::from_raw(regs.dx, 0))
+    }
+
+    /// Sets the last-access date (int 21h, AX=5705h). The time of day is discarded.
+    ///
+    /// Only DOS 7 and later support this, others fail with [`Error::InvalidFunction`].
+    pub fn set_accessed(&self, time: FileTime) -> Result<()> {
+        unsafe { int21(Registers { ax: 0x5705, bx: self.0, cx: 0, dx: time.date(), ..Registers::new() }) }?;
+        Ok(())
+    }
+
+    /// Returns the creation timestamp (int 21h, AX=5706h), without the 10 ms part DOS also keeps.
+    ///
+    /// Only DOS 7 and later support this, others fail with [`Error::InvalidFunction`].
+    pub fn created(&self) -> Result<FileTime> {
+        let regs = unsafe { int21(Registers { ax: 0x5706, bx: self.0, ..Registers::new() }) }?;
+        Ok(FileTime::from_raw(regs.dx, regs.cx))
+    }
+
+    /// Sets the creation timestamp (int 21h, AX=5707h).
+    ///
+    /// Only DOS 7 and later support this, others fail with [`Error::InvalidFunction`].
+    pub fn set_created(&self, time: FileTime) -> Result<()> {
+        unsafe { int21(Registers { ax: 0x5707, bx: self.0, cx: time.time(), dx: time.date(), si: 0, ..Registers::new() }) }?;
+        Ok(())
+    }
+
+    /// Closes the file (int 21h, AH=3Eh).
+    ///
+    /// Dropping a [`File`] closes it as well, but discards any error.
+    pub fn close(self) -> Result<()> {
+        let handle = self.0;
+        core::mem::forget(self);
+
+        unsafe { int21(Registers { ax: 0x3E00, bx: handle, ..Registers::new() }) }?;
+        Ok(())
     }
 }
 
 impl Drop for File {
     #[inline]
     fn drop(&mut self) {
-        unsafe { asm!("int 0x21", in("ah") 0x3Eu8, in("bx") self.0, options(nostack)) };
+        let _ = unsafe { int21(Registers { ax: 0x3E00, bx: self.0, ..Registers::new() }) };
     }
 }
 
+/// Renames or moves a file (int 21h, AH=56h).
 #[inline]
-pub fn rename_file(from: *const u8, to: *const u8) {
-    unsafe { asm!("int 0x21", in("ah") 0x56u8, in("dx") from, in("di") to, options(nostack)) }
+pub fn rename_file(from: *const u8, to: *const u8) -> Result<()> {
+    unsafe { int21(Registers { ax: 0x5600, dx: from as u16, di: to as u16, ..Registers::new() }) }?;
+    Ok(())
 }
 
+/// Deletes a file (int 21h, AH=41h).
 #[inline]
-pub fn remove_file(path: *const u8) {
-    unsafe { asm!("int 0x21", in("ah") 0x41u8, in("dx") path, options(nostack)) }
+pub fn remove_file(path: *const u8) -> Result<()> {
+    unsafe { int21(Registers { ax: 0x4100, dx: path as u16, ..Registers::new() }) }?;
+    Ok(())
 }
 
+/// Creates a directory (int 21h, AH=39h).
+#[inline]
+pub fn create_dir(path: *const u8) -> Result<()> {
+    unsafe { int21(Registers { ax: 0x3900, dx: path as u16, ..Registers::new() }) }?;
+    Ok(())
+}
+
+/// Removes an empty directory (int 21h, AH=3Ah).
+#[inline]
+pub fn remove_dir(path: *const u8) -> Result<()> {
+    unsafe { int21(Registers { ax: 0x3A00, dx: path as u16, ..Registers::new() }) }?;
+    Ok(())
+}
+
+/// Changes the current directory of the drive named in `path`, or of the default drive if
+/// `path` has no drive letter (int 21h, AH=3Bh).
+///
+/// The default drive itself is left unchanged, see [`drive::set_current`].
+///
+/// [`drive::set_current`]: crate::drive::set_current
+#[inline]
+pub fn set_current_dir(path: *const u8) -> Result<()> {
+    unsafe { int21(Registers { ax: 0x3B00, dx: path as u16, ..Registers::new() }) }?;
+    Ok(())
+}
+
+/// Returns the current directory of `drive`, or of the default drive if [`None`] (int 21h, AH=47h).
+///
+/// DOS keeps one current directory per drive. The returned path has neither a drive letter nor
+/// a leading backslash, so the root directory is returned as an empty path.
+pub fn current_dir(drive: Option<Drive>, buf: &mut [u8; 64]) -> Result<&[u8]> {
+    unsafe { int21(Registers { ax: 0x4700, dx: Drive::number(drive) as u16, si: buf.as_mut_ptr() as u16, ..Registers::new() }) }?;
+
+    let len = buf.iter().position(|&byte| byte == 0).unwrap_or(buf.len());
+    Ok(&buf[..len])
+}
+
+/// Returns an iterator over the directory entries matching `pattern` (int 21h, AH=4Eh/4Fh).
+///
+/// `pattern` is a path whose last component may contain the `?` and `*` wildcards, such as
+/// `C:\DOS\*.*`. Normal files are always matched; hidden, system, volume and directory
+/// entries are only matched if included in `flags`. A pattern that matches nothing yields an
+/// empty iterator.
+pub fn read_dir(pattern: *const u8, flags: Flags) -> Result<ReadDir> {
+    let mut dta = Dta::new();
+    let result = dta.with(|| unsafe { int21(Registers { ax: 0x4E00, cx: flags.0 as u16, dx: pattern as u16, ..Registers::new() }) });
+
+    match result {
+        Ok(_) => Ok(ReadDir { dta, first: true, done: false }),
+        Err(Error::FileNotFound | Error::NoMoreFiles) => Ok(ReadDir { dta, first: false, done: true }),
+        Err(error) => Err(error),
+    }
+}
+
+/// An iterator over the entries of a directory, see [`read_dir`].
+///
+/// Every iterator owns its Disk Transfer Area and only installs it for the duration of each
+/// search call, restoring the previous one afterwards, so enumerations can be nested.
+#[derive(Debug, Clone)]
+pub struct ReadDir {
+    dta: Dta,
+    first: bool,
+    done: bool,
+}
+
+impl Iterator for ReadDir {
+    type Item = Result<DirEntry>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.done {
+            return None;
+        }
+
+        if !core::mem::take(&mut self.first) {
+            match self.dta.with(|| unsafe { int21(Registers { ax: 0x4F00, ..Registers::new() }) }) {
+                Ok(_) => {}
+                Err(Error::NoMoreFiles) => {
+                    self.done = true;
+                    return None;
+                }
+                Err(error) => {
+                    self.done = true;
+                    return Some(Err(error));
+                }
+            }
+        }
+
+        Some(Ok(self.dta.entry()))
+    }
+}
+
+/// An entry returned by [`ReadDir`].
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct DirEntry {
+    name: [u8; 13],
+    flags: Flags,
+    size: u32,
+    date: u16,
+    time: u16,
+}
+
+impl DirEntry {
+    /// Returns the 8.3 file name, without the terminating NUL.
+    #[inline]
+    pub fn name(&self) -> &[u8] {
+        let len = self.name.iter().position(|&byte| byte == 0).unwrap_or(self.name.len());
+        &self.name[..len]
+    }
+
+    /// Returns the attributes of the entry.
+    #[inline]
+    pub const fn flags(&self) -> Flags {
+        self.flags
+    }
+
+    /// Returns `true` if the entry is a directory.
+    #[inline]
+    pub const fn is_dir(&self) -> bool {
+        self.flags.contains(Flags::D)
+    }
+
+    /// Returns the file size in bytes.
+    #[inline]
+    pub const fn size(&self) -> u32 {
+        self.size
+    }
+
+    /// Returns the packed date of the last write: bits 15-9 are the year since 1980,
+    /// bits 8-5 the month and bits 4-0 the day.
+    #[inline]
+    pub const fn date(&self) -> u16 {
+        self.date
+    }
+
+    /// Returns the packed time of the last write: bits 15-11 are the hours, bits 10-5
+    /// the minutes and bits 4-0 the seconds divided by two.
+    #[inline]
+    pub const fn time(&self) -> u16 {
+        self.time
+    }
+
+    /// Returns the timestamp of the last write.
+    #[inline]
+    pub const fn modified(&self) -> FileTime {
+        FileTime::from_raw(self.date, self.time)
+    }
+}
+
+/// The Disk Transfer Area as filled in by FindFirst (4Eh) and FindNext (4Fh).
+#[derive(Debug, Clone, Copy)]
+#[repr(C, packed)]
+struct Dta {
+    /// Search state private to DOS.
+    reserved: [u8; 21],
+    attributes: u8,
+    time: u16,
+    date: u16,
+    size: u32,
+    name: [u8; 13],
+}
+
+impl Dta {
+    #[inline]
+    const fn new() -> Self {
+        Self { reserved: [0; 21], attributes: 0, time: 0, date: 0, size: 0, name: [0; 13] }
+    }
+
+    /// Makes `self` the current DTA (int 21h, AH=1Ah) for the duration of `f`, then restores
+    /// the previous one.
+    fn with<R>(&mut self, f: impl FnOnce() -> R) -> R {
+        let mut previous = Registers { ax: 0x2F00, ..Registers::new() };
+        unsafe { bios::int::<0x21>(&mut previous) };
+
+        let mut regs = Registers { ax: 0x1A00, dx: self as *mut Self as u16, ..Registers::new() };
+        unsafe { bios::int::<0x21>(&mut regs) };
+
+        let result = f();
+
+        let mut regs = Registers { ax: 0x1A00, dx: previous.bx, ds: previous.es, ..Registers::new() };
+        unsafe { bios::int::<0x21>(&mut regs) };
+
+        result
+    }
+
+    #[inline]
+    const fn entry(&self) -> DirEntry {
+        DirEntry { name: self.name, flags: Flags(self.attributes), size: self.size, date: self.date, time: self.time }
+    }
+}
+
+/// The attributes of a directory entry.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
 #[repr(transparent)]
 pub struct Flags(u8);
 
 impl Flags {
+    /// No attributes, a normal file.
+    #[inline]
+    pub const fn empty() -> Self {
+        Self(0)
+    }
+
+    /// Every attribute.
+    #[inline]
+    pub const fn all() -> Self {
+        Self(0x3F)
+    }
+
     /// Read-only.
     pub const R: Self = Self(1 << 0);
     /// Hidden.
@@ -56,6 +411,57 @@ impl Flags {
     pub const D: Self = Self(1 << 4);
     /// Archive.
     pub const A: Self = Self(1 << 5);
+
+    /// Returns the flags from the raw attribute byte, dropping undefined bits.
+    #[inline]
+    pub const fn from_bits_truncate(bits: u8) -> Self {
+        Self(bits & Self::all().0)
+    }
+
+    /// Returns the raw attribute byte.
+    #[inline]
+    pub const fn bits(self) -> u8 {
+        self.0
+    }
+
+    #[inline]
+    pub const fn is_empty(self) -> bool {
+        self.0 == 0
+    }
+
+    /// Returns `true` if all of `other` is set in `self`.
+    #[inline]
+    pub const fn contains(self, other: Self) -> bool {
+        self.0 & other.0 == other.0
+    }
+
+    /// Returns `true` if any of `other` is set in `self`.
+    #[inline]
+    pub const fn intersects(self, other: Self) -> bool {
+        self.0 & other.0 != 0
+    }
+
+    #[inline]
+    pub const fn insert(&mut self, other: Self) {
+        self.0 |= other.0;
+    }
+
+    #[inline]
+    pub const fn remove(&mut self, other: Self) {
+        self.0 &= !other.0;
+    }
+
+    /// Inserts `other` if `value` is `true`, removes it otherwise.
+    #[inline]
+    pub const fn set(&mut self, other: Self, value: bool) {
+        if value { self.insert(other) } else { self.remove(other) }
+    }
+
+    /// Returns an iterator over the single attributes set in `self`.
+    #[inline]
+    pub const fn iter(self) -> Iter {
+        Iter(self.0)
+    }
 }
 
 impl BitOr for Flags {
@@ -74,16 +480,70 @@ impl BitOrAssign for Flags {
     }
 }
 
+impl BitAnd for Flags {
+    type Output = Self;
+
+    #[inline]
+    fn bitand(self, rhs: Self) -> Self::Output {
+        Self(self.0 & rhs.0)
+    }
+}
+
+impl BitAndAssign for Flags {
+    #[inline]
+    fn bitand_assign(&mut self, rhs: Self) {
+        self.0 &= rhs.0;
+    }
+}
+
+impl Not for Flags {
+    type Output = Self;
+
+    /// Returns the complement of `self`, limited to the defined attributes.
+    #[inline]
+    fn not(self) -> Self::Output {
+        Self::from_bits_truncate(!self.0)
+    }
+}
+
+impl IntoIterator for Flags {
+    type Item = Self;
+    type IntoIter = Iter;
+
+    #[inline]
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
+
+/// An iterator over the single attributes set in a [`Flags`], lowest bit first.
+#[derive(Debug, Clone)]
+pub struct Iter(u8);
+
+impl Iterator for Iter {
+    type Item = Flags;
+
+    #[inline]
+    fn next(&mut self) -> Option<Self::Item> {
+        let bits = self.0;
+        self.0 &= bits.wrapping_sub(1);
+
+        (bits != 0).then(|| Flags(bits & bits.wrapping_neg()))
+    }
+}
+
+/// Returns the attributes of a file or directory (int 21h, AX=4300h).
 #[inline]
-pub fn flags(path: *const u8) -> Flags {
-    let Flags(attributes);
-    unsafe { asm!("int 0x21", in("ah") 0x43u8, in("al") 0x00u8, in("dx") path, lateout("al") attributes, options(nostack)) };
-    Flags(attributes)
+pub fn flags(path: *const u8) -> Result<Flags> {
+    let regs = unsafe { int21(Registers { ax: 0x4300, dx: path as u16, ..Registers::new() }) }?;
+    Ok(Flags::from_bits_truncate(regs.cl()))
 }
 
-#[inline(always)]
-fn cf() -> bool {
-    let flags: u16;
-    unsafe { asm!("pushf", "pop {0:x}", out(reg) flags) }
-    (flags & 0x1) != 0
+/// Sets the attributes of a file or directory (int 21h, AX=4301h).
+///
+/// The directory and volume attributes cannot be changed this way and must not be set in `flags`.
+#[inline]
+pub fn set_flags(path: *const u8, flags: Flags) -> Result<()> {
+    unsafe { int21(Registers { ax: 0x4301, cx: flags.0 as u16, dx: path as u16, ..Registers::new() }) }?;
+    Ok(())
 }
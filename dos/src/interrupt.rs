@@ -0,0 +1,280 @@
+use crate::FarPtr;
+use bios::Registers;
+use bios::cpu::without_interrupts;
+use core::arch::asm;
+
+/// Returns the handler of interrupt `number` (int 21h, AH=35h).
+#[must_use]
+pub fn get_vector(number: u8) -> FarPtr<()> {
+    let mut regs = Registers { ax: 0x3500 | number as u16, ..Registers::new() };
+    unsafe { bios::int::<0x21>(&mut regs) };
+    FarPtr::new(regs.es, regs.bx)
+}
+
+/// Points interrupt `number` at `handler` (int 21h, AH=25h) and returns the previous handler.
+///
+/// # Safety
+///
+/// `handler` must be a valid interrupt handler for as long as it stays installed, and must
+/// be uninstalled before the program exits.
+pub unsafe fn set_vector(number: u8, handler: FarPtr<()>) -> FarPtr<()> {
+    without_interrupts(|| {
+        let previous = get_vector(number);
+        let mut regs = Registers { ax: 0x2500 | number as u16, dx: handler.offset(), ds: handler.segment(), ..Registers::new() };
+        unsafe { bios::int::<0x21>(&mut regs) };
+        previous
+    })
+}
+
+/// Returns the code segment, which for a `.COM` program is also the data and stack segment.
+#[inline]
+#[must_use]
+pub fn code_segment() -> u16 {
+    let segment: u16;
+    unsafe { asm!("mov {0:x}, cs", out(reg) segment, options(nomem, nostack, preserves_flags)) };
+    segment
+}
+
+/// The registers of the interrupted code, as saved by a handler declared with
+/// [`interrupt_handler!`]. Changes are written back when the handler returns or chains.
+#[repr(C)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct InterruptFrame {
+    pub edi: u32,
+    pub esi: u32,
+    pub ebp: u32,
+    /// Saved by `pushad` but not restored, the stack pointer cannot be changed.
+    esp: u32,
+    pub ebx: u32,
+    pub edx: u32,
+    pub ecx: u32,
+    pub eax: u32,
+    pub es: u16,
+    pub ds: u16,
+}
+
+/// What a handler declared with [`interrupt_handler!`] does once its function returns.
+#[repr(u8)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Action {
+    /// Returns to the interrupted code with `iret`. Hardware interrupts must have been
+    /// acknowledged at the interrupt controller.
+    Return = 0,
+    /// Jumps to the previously installed handler, which sees the interrupt as if it had been
+    /// raised directly.
+    Chain = 1,
+}
+
+/// An interrupt handler declared with [`interrupt_handler!`], ready to be installed with
+/// [`InterruptGuard::install`].
+#[derive(Debug)]
+pub struct Handler {
+    entry: extern "C" fn(),
+    /// The handler to chain to, as offset and segment for `ljmp`.
+    previous: *mut [u16; 2],
+}
+
+unsafe impl Sync for Handler {}
+
+impl Handler {
+    #[doc(hidden)]
+    #[inline]
+    #[must_use]
+    pub const unsafe fn __new(entry: extern "C" fn(), previous: *mut [u16; 2]) -> Self {
+        Self { entry, previous }
+    }
+
+    /// Returns the address of the generated entry code.
+    #[inline]
+    #[must_use]
+    pub fn vector(&self) -> FarPtr<()> {
+        FarPtr::new(code_segment(), self.entry as *const () as u16)
+    }
+
+    /// Returns the handler that [`Action::Chain`] jumps to.
+    #[inline]
+    #[must_use]
+    pub fn previous(&self) -> FarPtr<()> {
+        let [offset, segment] = unsafe { *self.previous };
+        FarPtr::new(segment, offset)
+    }
+}
+
+/// An installed interrupt handler. Dropping the guard puts the previous handler back.
+///
+/// Guards for the same interrupt must be dropped in the reverse order they were created in.
+#[derive(Debug)]
+#[must_use = "dropping the guard uninstalls the handler"]
+pub struct InterruptGuard {
+    number: u8,
+    previous: FarPtr<()>,
+}
+
+impl InterruptGuard {
+    /// Points interrupt `number` at `vector` until the guard is dropped.
+    ///
+    /// # Safety
+    ///
+    /// See [`set_vector`]. The guard must not be leaked.
+    pub unsafe fn new(number: u8, vector: FarPtr<()>) -> Self {
+        let previous = unsafe { set_vector(number, vector) };
+        Self { number, previous }
+    }
+
+    /// Installs `handler` for interrupt `number` until the guard is dropped, chaining to the
+    /// handler that was installed before.
+    ///
+    /// # Safety
+    ///
+    /// The guard must not be leaked, and `handler` must not be installed anywhere else at the
+    /// same time.
+    pub unsafe fn install(number: u8, handler: &'static Handler) -> Self {
+        without_interrupts(|| {
+            let previous = get_vector(number);
+            unsafe { *handler.previous = [previous.offset(), previous.segment()] };
+            unsafe { Self::new(number, handler.vector()) }
+        })
+    }
+
+    /// Returns the interrupt number.
+    #[inline]
+    #[must_use]
+    pub const fn number(&self) -> u8 {
+        self.number
+    }
+
+    /// Returns the handler that is put back on drop.
+    #[inline]
+    #[must_use]
+    pub const fn previous(&self) -> FarPtr<()> {
+        self.previous
+    }
+}
+
+impl Drop for InterruptGuard {
+    fn drop(&mut self) {
+        unsafe { set_vector(self.number, self.previous) };
+    }
+}
+
+/// Declares a [`Handler`] static running a Rust function as an interrupt handler.
+///
+/// The function must have the signature `extern "C" fn(&mut InterruptFrame) -> Action`. The
+/// generated entry code saves all registers, points `DS` and `ES` at our segment and clears
+/// the direction flag, calls the function, restores the registers from the frame, then returns
+/// or chains as told.
+///
+/// With `stack = SIZE`, the function runs on a private stack of `SIZE` bytes, so it can
+/// interrupt code running on any stack. Without it, the function runs on the interrupted
+/// stack, which only works for interrupts raised while `SS` is our segment, such as software
+/// interrupts the program calls itself.
+///
+/// Interrupts stay disabled unless the function enables them. The handler is not reentrant.
+///
+/// ```ignore
+/// use dos::interrupt::{Action, InterruptFrame, InterruptGuard};
+///
+/// extern "C" fn on_tick(_frame: &mut InterruptFrame) -> Action {
+///     Action::Chain
+/// }
+///
+/// dos::interrupt_handler! {
+///     static TICK = on_tick, stack = 512;
+/// }
+///
+/// let _guard = unsafe { InterruptGuard::install(0x1C, &TICK) };
+/// ```
+#[macro_export]
+macro_rules! interrupt_handler {
+    ($(#[$attr:meta])* $vis:vis static $name:ident = $callback:path $(, stack = $stack:expr)? $(,)?;) => {
+        $(#[$attr])*
+        $vis static $name: $crate::interrupt::Handler = {
+            const _: extern "C" fn(&mut $crate::interrupt::InterruptFrame) -> $crate::interrupt::Action = $callback;
+
+            static mut PREVIOUS: [u16; 2] = [0; 2];
+            static mut ACTION: u8 = 0;
+
+            $crate::interrupt_handler!(@entry $callback $(, $stack)?);
+
+            unsafe { $crate::interrupt::Handler::__new(entry, &raw mut PREVIOUS) }
+        };
+    };
+    (@entry $callback:path) => {
+        #[unsafe(naked)]
+        extern "C" fn entry() {
+            core::arch::naked_asm!(
+                "push ds",
+                "push es",
+                "pushad",
+                "push cs",
+                "pop ds",
+                "push cs",
+                "pop es",
+                "cld",
+                "push esp",
+                "call {callback}",
+                "add esp, 4",
+                "mov byte ptr [{action}], al",
+                "popad",
+                "pop es",
+                "pop ds",
+                "cmp byte ptr cs:[{action}], 0",
+                "jne 2f",
+                "iret",
+                "2:",
+                "ljmp cs:[{previous}]",
+                callback = sym $callback,
+                action = sym ACTION,
+                previous = sym PREVIOUS,
+            );
+        }
+    };
+    (@entry $callback:path, $stack:expr) => {
+        #[repr(C, align(16))]
+        struct Stack([u8; $stack]);
+
+        static mut STACK: Stack = Stack([0; $stack]);
+        static mut SAVED_SS: u16 = 0;
+        static mut SAVED_ESP: u32 = 0;
+
+        #[unsafe(naked)]
+        extern "C" fn entry() {
+            core::arch::naked_asm!(
+                "mov word ptr cs:[{saved_ss}], ss",
+                "mov dword ptr cs:[{saved_esp}], esp",
+                "push cs",
+                "pop ss",
+                "mov esp, offset {stack} + {stack_size}",
+                "push ds",
+                "push es",
+                "pushad",
+                "push cs",
+                "pop ds",
+                "push cs",
+                "pop es",
+                "cld",
+                "push esp",
+                "call {callback}",
+                "add esp, 4",
+                "mov byte ptr [{action}], al",
+                "popad",
+                "pop es",
+                "pop ds",
+                "mov ss, word ptr cs:[{saved_ss}]",
+                "mov esp, dword ptr cs:[{saved_esp}]",
+                "cmp byte ptr cs:[{action}], 0",
+                "jne 2f",
+                "iret",
+                "2:",
+                "ljmp cs:[{previous}]",
+                saved_ss = sym SAVED_SS,
+                saved_esp = sym SAVED_ESP,
+                stack = sym STACK,
+                stack_size = const $stack,
+                callback = sym $callback,
+                action = sym ACTION,
+                previous = sym PREVIOUS,
+            );
+        }
+    };
+}
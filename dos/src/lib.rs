@@ -1,9 +1,25 @@
 #![no_std]
 
+use bios::Registers;
+use core::arch::asm;
 use core::marker::PhantomData;
+use core::mem::MaybeUninit;
 
+mod error;
+
+pub mod break_handler;
+pub mod critical_error;
+pub mod drive;
+pub mod ems;
 pub mod fs;
+pub mod interrupt;
+pub mod memory;
+pub mod process;
 pub mod time;
+pub mod timer;
+pub mod xms;
+
+pub use error::{Error, ErrorAction, ErrorClass, ErrorLocus, ExtendedError};
 
 #[repr(C, packed)]
 #[derive(Debug, Clone, Copy)]
@@ -93,60 +109,101 @@ impl<T> FarPtr<T> {
     pub const fn as_ptr(self) -> *mut T {
         (((self.segment as u32) << 4) + (self.offset as u32)) as *mut T
     }
+
+    /// Returns the far address of `pointer`, an offset into [`memory::data_segment`].
+    ///
+    /// [`memory::data_segment`]: crate::memory::data_segment
+    #[inline]
+    #[must_use]
+    pub fn from_ptr(pointer: *const T) -> Self {
+        Self::new(memory::data_segment(), pointer as u16)
+    }
+
+    #[inline]
+    #[must_use]
+    pub const fn segment(self) -> u16 {
+        self.segment
+    }
+
+    #[inline]
+    #[must_use]
+    pub const fn offset(self) -> u16 {
+        self.offset
+    }
+
+    /// Reads the value at this address, which may lie outside our segment.
+    ///
+    /// # Safety
+    ///
+    /// See [`core::ptr::read`]. The value must not wrap around the end of its segment.
+    pub unsafe fn read(self) -> T {
+        let mut value = MaybeUninit::<T>::uninit();
+
+        unsafe {
+            far_copy(FarPtr::new(self.segment, self.offset), FarPtr::from_ptr(value.as_mut_ptr().cast()), size_of::<T>() as u16);
+            value.assume_init()
+        }
+    }
 }
 
-#[derive(thiserror::Error, Debug)]
-#[repr(u8)]
-pub enum Error {
-    #[error("Invalid function.")]
-    InvalidFunction = 0x01,
-    #[error("File not found.")]
-    FileNotFound = 0x02,
-    #[error("Path not found.")]
-    PathNotFound = 0x03,
-    #[error("Too many open files.")]
-    TooManyOpenFiles = 0x04,
-    #[error("Access denied.")]
-    AccessDenied = 0x05,
-    #[error("Invalid handle.")]
-    InvalidHandle = 0x06,
-    #[error("Insufficient memory.")]
-    InsufficientMemory = 0x08,
-    #[error("Invalid format.")]
-    InvalidFormat = 0x0B,
-    #[error("Invalid access code.")]
-    InvalidAccessCode = 0x0C,
-    #[error("Invalid drive.")]
-    InvalidDrive = 0x0F,
-    #[error("Printer out of paper.")]
-    PrinterOutOfPaper = 0x1C,
-    #[error("Write fault.")]
-    WriteFault = 0x1D,
-    #[error("Read fault.")]
-    ReadFault = 0x1E,
-    #[error("General failure.")]
-    GeneralFailure = 0x1F,
-    #[error("Sharing violation.")]
-    SharingViolation = 0x20,
-    #[error("Lock violation.")]
-    LockViolation = 0x21,
-    #[error("Disk change invalid.")]
-    DiskChangeInvalid = 0x22,
-    #[error("Invalid sharing buffer.")]
-    InvalidSharingBuffer = 0x24,
-    #[error("Code page mismatch.")]
-    CodePageMismatch = 0x25,
-    #[error("File exists.")]
-    FileExists = 0x50,
-    #[error("Invalid password.")]
-    InvalidPassword = 0x56,
-    #[error("Invalid parameter.")]
-    InvalidParameter = 0x57,
+impl FarPtr<u8> {
+    /// Copies `buf.len()` bytes from this address into `buf`.
+    ///
+    /// # Safety
+    ///
+    /// The bytes must be readable and must not wrap around the end of their segment.
+    pub unsafe fn copy_to_slice(self, buf: &mut [u8]) {
+        unsafe { far_copy(self, FarPtr::from_ptr(buf.as_mut_ptr()), buf.len() as u16) };
+    }
+
+    /// Copies `buf` to this address.
+    ///
+    /// # Safety
+    ///
+    /// The bytes must be writable and must not wrap around the end of their segment.
+    pub unsafe fn copy_from_slice(self, buf: &[u8]) {
+        unsafe { far_copy(FarPtr::from_ptr(buf.as_ptr()), self, buf.len() as u16) };
+    }
 }
 
-impl Error {
-    #[inline(always)]
-    unsafe fn new_unchecked(value: u8) -> Self {
-        unsafe { core::mem::transmute(value) }
+/// Copies `count` bytes from `source` to `destination`, either of which may lie outside our segment.
+///
+/// # Safety
+///
+/// The bytes must be readable at `source`, writable at `destination`, and must not wrap around
+/// the end of their segments.
+unsafe fn far_copy(source: FarPtr<u8>, destination: FarPtr<u8>, count: u16) {
+    unsafe {
+        asm!(
+            // LLVM reserves ESI, so it is saved here instead of being an operand.
+            "push esi",
+            "push ds",
+            "push es",
+            "mov si, {source_offset:x}",
+            "mov es, {destination_segment:x}",
+            "mov ds, {source_segment:x}",
+            "rep movsb",
+            "pop es",
+            "pop ds",
+            "pop esi",
+            source_segment = in(reg) source.segment,
+            source_offset = in(reg) source.offset,
+            destination_segment = in(reg) destination.segment,
+            inout("di") destination.offset => _,
+            inout("cx") count => _,
+            options(preserves_flags),
+        );
     }
 }
+
+/// Calls the DOS function dispatcher (int 21h) with `regs`, mapping a set carry flag to the [`Error`] in AX.
+///
+/// # Safety
+///
+/// See [`bios::int`].
+#[inline]
+pub(crate) unsafe fn int21(mut regs: Registers) -> Result<Registers, Error> {
+    unsafe { bios::int::<0x21>(&mut regs) };
+
+    if regs.carry() { Err(Error::from(regs.al())) } else { Ok(regs) }
+}
@@ -0,0 +1,49 @@
+use crate::{Error, int21};
+use bios::Registers;
+use core::arch::asm;
+
+pub type Result<T> = core::result::Result<T, Error>;
+
+/// Bytes in a paragraph, the unit DOS allocates memory in.
+pub const PARAGRAPH: usize = 16;
+
+/// Allocates a block of `paragraphs` paragraphs (int 21h, AH=48h) and returns its segment.
+pub fn allocate(paragraphs: u16) -> Result<u16> {
+    unsafe { int21(Registers { ax: 0x4800, bx: paragraphs, ..Registers::new() }) }.map(|regs| regs.ax)
+}
+
+/// Returns the size of the largest free block in paragraphs (int 21h, AH=48h, BX=FFFFh).
+#[must_use]
+pub fn largest_free() -> u16 {
+    let mut regs = Registers { ax: 0x4800, bx: 0xFFFF, ..Registers::new() };
+    unsafe { bios::int::<0x21>(&mut regs) };
+    regs.bx
+}
+
+/// Frees the block at `segment` (int 21h, AH=49h).
+///
+/// # Safety
+///
+/// The block must have been allocated with [`allocate`] and must not be used afterwards.
+pub unsafe fn free(segment: u16) -> Result<()> {
+    unsafe { int21(Registers { ax: 0x4900, es: segment, ..Registers::new() }) }.map(|_| ())
+}
+
+/// Resizes the block at `segment` in place to `paragraphs` paragraphs (int 21h, AH=4Ah).
+///
+/// # Safety
+///
+/// Nothing beyond the new size of the block may be used afterwards.
+pub unsafe fn resize(segment: u16, paragraphs: u16) -> Result<()> {
+    unsafe { int21(Registers { ax: 0x4A00, bx: paragraphs, es: segment, ..Registers::new() }) }.map(|_| ())
+}
+
+/// Returns the segment that pointers are offsets into, which for a `.COM` program is also
+/// the segment of its PSP and of its memory block.
+#[inline]
+#[must_use]
+pub fn data_segment() -> u16 {
+    let segment: u16;
+    unsafe { asm!("mov {0:x}, ds", out(reg) segment, options(nomem, nostack, preserves_flags)) };
+    segment
+}
@@ -0,0 +1,39 @@
+use bios::Registers;
+
+/// Cleanup that must run before the program returns to DOS, such as restoring hooked
+/// interrupt vectors, most recently registered first.
+static mut EXIT_HOOKS: [Option<fn()>; 8] = [None; 8];
+
+/// Registers `hook` to run on exit, returning its slot for [`remove_exit_hook`], or [`None`]
+/// if all slots are taken.
+pub(crate) fn add_exit_hook(hook: fn()) -> Option<usize> {
+    let slot = unsafe { EXIT_HOOKS }.iter().position(Option::is_none)?;
+    unsafe { EXIT_HOOKS[slot] = Some(hook) };
+    Some(slot)
+}
+
+/// Unregisters the hook in `slot`, as returned by [`add_exit_hook`].
+pub(crate) fn remove_exit_hook(slot: usize) {
+    unsafe { EXIT_HOOKS[slot] = None };
+}
+
+/// Runs and unregisters every exit hook. Called by the runtime after `main` returns.
+#[unsafe(no_mangle)]
+pub extern "C" fn __dos_exit_hooks() {
+    for slot in (0..unsafe { EXIT_HOOKS }.len()).rev() {
+        if let Some(hook) = unsafe { EXIT_HOOKS[slot] } {
+            unsafe { EXIT_HOOKS[slot] = None };
+            hook();
+        }
+    }
+}
+
+/// Terminates the program with the given exit code (int 21h, AH=4Ch), after undoing
+/// everything this crate has hooked.
+pub fn exit(code: u8) -> ! {
+    __dos_exit_hooks();
+
+    let mut regs = Registers { ax: 0x4C00 | code as u16, ..Registers::new() };
+    unsafe { bios::int::<0x21>(&mut regs) };
+    unreachable!("int 21h, AH=4Ch returned")
+}
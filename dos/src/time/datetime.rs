@@ -0,0 +1,596 @@
+use super::{FileTime, civil_from_days, days_from_civil, days_in_month};
+use bios::Registers;
+use core::fmt::{self, Display, Write};
+use core::str::FromStr;
+
+const MONTHS: [&str; 12] = ["January", "February", "March", "April", "May", "June", "July", "August", "September", "October", "November", "December"];
+
+/// A day of the week, numbered from Sunday as DOS does.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[repr(u8)]
+pub enum Weekday {
+    Sunday = 0,
+    Monday = 1,
+    Tuesday = 2,
+    Wednesday = 3,
+    Thursday = 4,
+    Friday = 5,
+    Saturday = 6,
+}
+
+impl Weekday {
+    const ALL: [Self; 7] = [Self::Sunday, Self::Monday, Self::Tuesday, Self::Wednesday, Self::Thursday, Self::Friday, Self::Saturday];
+
+    /// Returns the weekday numbered `day` from Sunday, or [`None`] if `day` is above 6.
+    #[inline]
+    #[must_use]
+    pub const fn from_sunday(day: u8) -> Option<Self> {
+        if day < 7 { Some(Self::ALL[day as usize]) } else { None }
+    }
+
+    /// Returns the full English name, such as `Monday`.
+    #[must_use]
+    pub const fn name(self) -> &'static str {
+        match self {
+            Self::Sunday => "Sunday",
+            Self::Monday => "Monday",
+            Self::Tuesday => "Tuesday",
+            Self::Wednesday => "Wednesday",
+            Self::Thursday => "Thursday",
+            Self::Friday => "Friday",
+            Self::Saturday => "Saturday",
+        }
+    }
+}
+
+impl Display for Weekday {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.name())
+    }
+}
+
+/// A date and time of day, broken down into its fields.
+///
+/// Like everything on DOS this is local time, with a resolution of a hundredth of a second.
+/// Dates range from 1970-01-01 to 9999-12-31.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct DateTime {
+    year: u16,
+    month: u8,
+    day: u8,
+    hour: u8,
+    minute: u8,
+    second: u8,
+    hundredths: u8,
+}
+
+impl DateTime {
+    /// Seconds from 1970-01-01 to 10000-01-01.
+    const UNIX_END: u64 = 253_402_300_800;
+
+    /// Creates a date and time from its fields, or returns [`None`] if they are out of range.
+    #[must_use]
+    pub const fn new(year: u16, month: u8, day: u8, hour: u8, minute: u8, second: u8, hundredths: u8) -> Option<Self> {
+        if year < 1970 || year > 9999 || month < 1 || month > 12 || day < 1 || day > days_in_month(year, month) {
+            return None;
+        }
+
+        if hour > 23 || minute > 59 || second > 59 || hundredths > 99 {
+            return None;
+        }
+
+        Some(Self { year, month, day, hour, minute, second, hundredths })
+    }
+
+    /// Returns the current date and time from the DOS clock (int 21h, AH=2Ah and AH=2Ch).
+    ///
+    /// The date is read again after the time, so a midnight rollover in between is not
+    /// mistaken for a time almost a day off.
+    #[must_use]
+    pub fn now() -> Self {
+        let read = |ax: u16| {
+            let mut regs = Registers { ax, ..Registers::new() };
+            unsafe { bios::int::<0x21>(&mut regs) };
+            regs
+        };
+
+        loop {
+            let date = read(0x2A00);
+            let time = read(0x2C00);
+
+            if read(0x2A00) == date {
+                return Self {
+                    year: date.cx,
+                    month: date.dh(),
+                    day: date.dl(),
+                    hour: time.ch(),
+                    minute: time.cl(),
+                    second: time.dh(),
+                    hundredths: time.dl(),
+                };
+            }
+        }
+    }
+
+    /// Creates a date and time from the packed BCD values the real-time clock returns, as in
+    /// int 1Ah, AH=02h and AH=04h, or returns [`None`] if they are not valid.
+    #[must_use]
+    pub const fn from_bcd(century: u8, year: u8, month: u8, day: u8, hour: u8, minute: u8, second: u8) -> Option<Self> {
+        use bios::rtc::from_bcd;
+
+        match (from_bcd(century), from_bcd(year), from_bcd(month), from_bcd(day), from_bcd(hour), from_bcd(minute), from_bcd(second)) {
+            (Some(century), Some(year), Some(month), Some(day), Some(hour), Some(minute), Some(second)) => {
+                Self::new(century as u16 * 100 + year as u16, month, day, hour, minute, second, 0)
+            }
+            _ => None,
+        }
+    }
+
+    /// Returns the date and time `secs` seconds and `hundredths` after 1970-01-01 00:00:00,
+    /// or [`None`] if it lies after 9999.
+    #[must_use]
+    pub const fn from_unix(secs: u64, hundredths: u8) -> Option<Self> {
+        if secs >= Self::UNIX_END || hundredths > 99 {
+            return None;
+        }
+
+        let (year, month, day) = civil_from_days((secs / 86_400) as u32);
+        let secs = (secs % 86_400) as u32;
+        Some(Self { year, month, day, hour: (secs / 3600) as u8, minute: (secs / 60 % 60) as u8, second: (secs % 60) as u8, hundredths })
+    }
+
+    /// Returns the whole seconds since 1970-01-01 00:00:00, treating the local time as UTC.
+    /// The hundredths are left out, see [`DateTime::hundredths`].
+    #[must_use]
+    pub const fn to_unix(&self) -> u64 {
+        let days = days_from_civil(self.year, self.month, self.day) as u64;
+        days * 86_400 + self.hour as u64 * 3600 + self.minute as u64 * 60 + self.second as u64
+    }
+
+    #[inline]
+    #[must_use]
+    pub const fn year(&self) -> u16 {
+        self.year
+    }
+
+    /// Returns the month, from 1 to 12.
+    #[inline]
+    #[must_use]
+    pub const fn month(&self) -> u8 {
+        self.month
+    }
+
+    /// Returns the day of the month, from 1.
+    #[inline]
+    #[must_use]
+    pub const fn day(&self) -> u8 {
+        self.day
+    }
+
+    #[inline]
+    #[must_use]
+    pub const fn weekday(&self) -> Weekday {
+        // 1970-01-01 was a Thursday.
+        Weekday::ALL[((days_from_civil(self.year, self.month, self.day) + 4) % 7) as usize]
+    }
+
+    /// Returns the day of the year, from 1.
+    #[must_use]
+    pub const fn ordinal(&self) -> u16 {
+        (days_from_civil(self.year, self.month, self.day) - days_from_civil(self.year, 1, 1) + 1) as u16
+    }
+
+    #[inline]
+    #[must_use]
+    pub const fn hour(&self) -> u8 {
+        self.hour
+    }
+
+    #[inline]
+    #[must_use]
+    pub const fn minute(&self) -> u8 {
+        self.minute
+    }
+
+    #[inline]
+    #[must_use]
+    pub const fn second(&self) -> u8 {
+        self.second
+    }
+
+    #[inline]
+    #[must_use]
+    pub const fn hundredths(&self) -> u8 {
+        self.hundredths
+    }
+
+    /// Returns a value that displays `self` according to the strftime-style `format`.
+    ///
+    /// | Specifier | Replaced by |
+    /// |-----------|-------------|
+    /// | `%Y` | Year, as in `1994` |
+    /// | `%y` | Year without the century, `00` to `99` |
+    /// | `%m` | Month, `01` to `12` |
+    /// | `%B` | Month name, as in `January` |
+    /// | `%b` | Abbreviated month name, as in `Jan` |
+    /// | `%d` | Day of the month, `01` to `31` |
+    /// | `%j` | Day of the year, `001` to `366` |
+    /// | `%A` | Weekday name, as in `Sunday` |
+    /// | `%a` | Abbreviated weekday name, as in `Sun` |
+    /// | `%w` | Weekday number, `0` (Sunday) to `6` |
+    /// | `%H` | Hour, `00` to `23` |
+    /// | `%I` | Hour, `01` to `12` |
+    /// | `%p` | `AM` or `PM` |
+    /// | `%M` | Minute, `00` to `59` |
+    /// | `%S` | Second, `00` to `59` |
+    /// | `%f` | Hundredths of a second, `00` to `99` |
+    /// | `%F` | Same as `%Y-%m-%d` |
+    /// | `%T` | Same as `%H:%M:%S` |
+    /// | `%%` | A literal `%` |
+    ///
+    /// Any other specifier is written out unchanged.
+    #[inline]
+    #[must_use]
+    pub const fn format<'a>(&'a self, format: &'a str) -> Format<'a> {
+        Format { datetime: self, format }
+    }
+
+    /// Parses `input` according to the strftime-style `format`, see [`DateTime::format`].
+    ///
+    /// The date defaults to 1980-01-01 and the time to midnight where `format` leaves them out.
+    /// `%j`, `%A`, `%a` and `%w` are not supported.
+    pub fn parse(input: &str, format: &str) -> Result<Self, ParseError> {
+        let mut parser = Parser { input: input.as_bytes() };
+        let (mut year, mut month, mut day) = (1980, 1, 1);
+        let (mut hour, mut minute, mut second, mut hundredths) = (0, 0, 0, 0);
+        let mut pm = None;
+
+        let mut format = format.bytes();
+        while let Some(byte) = format.next() {
+            if byte != b'%' {
+                parser.literal(byte)?;
+                continue;
+            }
+
+            match format.next().ok_or(ParseError::InvalidFormat)? {
+                b'Y' => year = parser.number(4, 4)?,
+                // Years below 70 are in the 21st century, as POSIX specifies.
+                b'y' => {
+                    year = match parser.number(2, 2)? {
+                        year @ 0..70 => 2000 + year,
+                        year => 1900 + year,
+                    }
+                }
+                b'm' => month = parser.number(1, 2)? as u8,
+                b'B' => month = parser.month_name(false)?,
+                b'b' => month = parser.month_name(true)?,
+                b'd' => day = parser.number(1, 2)? as u8,
+                b'H' => hour = parser.number(1, 2)? as u8,
+                b'I' => match parser.number(1, 2)? {
+                    hour_of_12 @ 1..=12 => hour = hour_of_12 as u8 % 12,
+                    _ => return Err(ParseError::OutOfRange),
+                },
+                b'p' => pm = Some(parser.meridiem()?),
+                b'M' => minute = parser.number(1, 2)? as u8,
+                b'S' => second = parser.number(1, 2)? as u8,
+                b'f' => hundredths = parser.number(2, 2)? as u8,
+                b'F' => {
+                    year = parser.number(4, 4)?;
+                    parser.literal(b'-')?;
+                    month = parser.number(1, 2)? as u8;
+                    parser.literal(b'-')?;
+                    day = parser.number(1, 2)? as u8;
+                }
+                b'T' => {
+                    hour = parser.number(1, 2)? as u8;
+                    parser.literal(b':')?;
+                    minute = parser.number(1, 2)? as u8;
+                    parser.literal(b':')?;
+                    second = parser.number(1, 2)? as u8;
+                }
+                b'%' => parser.literal(b'%')?,
+                _ => return Err(ParseError::InvalidFormat),
+            }
+        }
+
+        if !parser.input.is_empty() {
+            return Err(ParseError::TrailingInput);
+        }
+
+        if pm == Some(true) && hour < 12 {
+            hour += 12;
+        }
+
+        Self::new(year, month, day, hour, minute, second, hundredths).ok_or(ParseError::OutOfRange)
+    }
+}
+
+impl Display for DateTime {
+    /// Formats as `%Y-%m-%d %H:%M:%S`.
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{:04}-{:02}-{:02} {:02}:{:02}:{:02}", self.year, self.month, self.day, self.hour, self.minute, self.second)
+    }
+}
+
+impl FromStr for DateTime {
+    type Err = ParseError;
+
+    /// Parses `%Y-%m-%d`, optionally followed by a space or `T` and `%H:%M`, `%H:%M:%S` or
+    /// `%H:%M:%S.%f`.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        const FORMATS: [&str; 7] = [
+            "%Y-%m-%d",
+            "%Y-%m-%d %H:%M",
+            "%Y-%m-%d %H:%M:%S",
+            "%Y-%m-%d %H:%M:%S.%f",
+            "%Y-%m-%dT%H:%M",
+            "%Y-%m-%dT%H:%M:%S",
+            "%Y-%m-%dT%H:%M:%S.%f",
+        ];
+
+        let mut error = ParseError::Invalid;
+        for format in FORMATS {
+            match Self::parse(s, format) {
+                Ok(datetime) => return Ok(datetime),
+                Err(ParseError::OutOfRange) => return Err(ParseError::OutOfRange),
+                Err(next) => error = next,
+            }
+        }
+
+        Err(error)
+    }
+}
+
+impl From<FileTime> for DateTime {
+    /// Converts a file timestamp, clamping the out of range fields broken software leaves behind.
+    fn from(time: FileTime) -> Self {
+        Self::from_unix(time.to_unix(), 0).expect("file timestamps end in 2107")
+    }
+}
+
+impl TryFrom<DateTime> for FileTime {
+    type Error = RangeError;
+
+    /// Converts to a file timestamp, rounding down to two seconds. Fails before 1980 or after 2107.
+    fn try_from(datetime: DateTime) -> Result<Self, Self::Error> {
+        Self::new(datetime.year, datetime.month, datetime.day, datetime.hour, datetime.minute, datetime.second).ok_or(RangeError)
+    }
+}
+
+/// Displays a [`DateTime`] according to a strftime-style format, see [`DateTime::format`].
+#[derive(Debug, Clone, Copy)]
+pub struct Format<'a> {
+    datetime: &'a DateTime,
+    format: &'a str,
+}
+
+impl Display for Format<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let datetime = self.datetime;
+        let month = MONTHS[datetime.month as usize - 1];
+        let weekday = datetime.weekday().name();
+
+        let mut chars = self.format.chars();
+        while let Some(char) = chars.next() {
+            if char != '%' {
+                f.write_char(char)?;
+                continue;
+            }
+
+            match chars.next() {
+                Some('Y') => write!(f, "{:04}", datetime.year)?,
+                Some('y') => write!(f, "{:02}", datetime.year % 100)?,
+                Some('m') => write!(f, "{:02}", datetime.month)?,
+                Some('B') => f.write_str(month)?,
+                Some('b') => f.write_str(&month[..3])?,
+                Some('d') => write!(f, "{:02}", datetime.day)?,
+                Some('j') => write!(f, "{:03}", datetime.ordinal())?,
+                Some('A') => f.write_str(weekday)?,
+                Some('a') => f.write_str(&weekday[..3])?,
+                Some('w') => write!(f, "{}", datetime.weekday() as u8)?,
+                Some('H') => write!(f, "{:02}", datetime.hour)?,
+                Some('I') => write!(f, "{:02}", (datetime.hour + 11) % 12 + 1)?,
+                Some('p') => f.write_str(if datetime.hour < 12 { "AM" } else { "PM" })?,
+                Some('M') => write!(f, "{:02}", datetime.minute)?,
+                Some('S') => write!(f, "{:02}", datetime.second)?,
+                Some('f') => write!(f, "{:02}", datetime.hundredths)?,
+                Some('F') => write!(f, "{:04}-{:02}-{:02}", datetime.year, datetime.month, datetime.day)?,
+                Some('T') => write!(f, "{:02}:{:02}:{:02}", datetime.hour, datetime.minute, datetime.second)?,
+                Some('%') => f.write_char('%')?,
+                Some(other) => write!(f, "%{other}")?,
+                None => f.write_char('%')?,
+            }
+        }
+
+        Ok(())
+    }
+}
+
+/// An error returned when a date and time cannot be represented in the requested form.
+#[derive(thiserror::Error, Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[error("date and time out of range")]
+pub struct RangeError;
+
+/// An error returned when parsing a [`DateTime`] fails.
+#[derive(thiserror::Error, Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum ParseError {
+    /// The input does not match the format.
+    #[error("input does not match the format")]
+    Invalid,
+    /// The input matches the format but names a date or time that does not exist.
+    #[error("date or time out of range")]
+    OutOfRange,
+    /// The input continues after the format has been matched.
+    #[error("trailing input")]
+    TrailingInput,
+    /// The format contains an unsupported specifier.
+    #[error("unsupported format specifier")]
+    InvalidFormat,
+}
+
+struct Parser<'a> {
+    input: &'a [u8],
+}
+
+impl Parser<'_> {
+    fn literal(&mut self, expected: u8) -> Result<(), ParseError> {
+        match self.input.split_first() {
+            Some((&byte, rest)) if byte == expected => {
+                self.input = rest;
+                Ok(())
+            }
+            _ => Err(ParseError::Invalid),
+        }
+    }
+
+    /// Parses between `min` and `max` decimal digits.
+    fn number(&mut self, min: usize, max: usize) -> Result<u16, ParseError> {
+        let len = self.input.iter().take(max).take_while(|byte| byte.is_ascii_digit()).count();
+        if len < min {
+            return Err(ParseError::Invalid);
+        }
+
+        let (digits, rest) = self.input.split_at(len);
+        self.input = rest;
+        Ok(digits.iter().fold(0, |value, digit| value * 10 + (digit - b'0') as u16))
+    }
+
+    /// Parses an English month name, returning the month from 1.
+    fn month_name(&mut self, abbreviated: bool) -> Result<u8, ParseError> {
+        for (month, name) in (1..).zip(MONTHS) {
+            let name = if abbreviated { &name[..3] } else { name };
+
+            if let Some(prefix) = self.input.get(..name.len())
+                && prefix.eq_ignore_ascii_case(name.as_bytes())
+            {
+                self.input = &self.input[name.len()..];
+                return Ok(month);
+            }
+        }
+
+        Err(ParseError::Invalid)
+    }
+
+    /// Parses `AM` or `PM`, returning `true` for `PM`.
+    fn meridiem(&mut self) -> Result<bool, ParseError> {
+        let pm = match self.input.get(..2) {
+            Some(meridiem) if meridiem.eq_ignore_ascii_case(b"AM") => false,
+            Some(meridiem) if meridiem.eq_ignore_ascii_case(b"PM") => true,
+            _ => return Err(ParseError::Invalid),
+        };
+
+        self.input = &self.input[2..];
+        Ok(pm)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    extern crate alloc;
+
+    use super::*;
+    use alloc::format;
+    use alloc::string::ToString;
+
+    fn datetime(year: u16, month: u8, day: u8, hour: u8, minute: u8, second: u8, hundredths: u8) -> DateTime {
+        DateTime::new(year, month, day, hour, minute, second, hundredths).unwrap()
+    }
+
+    #[test]
+    fn new_checks_ranges() {
+        assert!(DateTime::new(1970, 1, 1, 0, 0, 0, 0).is_some());
+        assert!(DateTime::new(9999, 12, 31, 23, 59, 59, 99).is_some());
+        assert_eq!(DateTime::new(1969, 12, 31, 0, 0, 0, 0), None);
+        assert_eq!(DateTime::new(1999, 2, 29, 0, 0, 0, 0), None);
+        assert_eq!(DateTime::new(2000, 1, 1, 0, 0, 0, 100), None);
+    }
+
+    #[test]
+    fn unix_round_trip() {
+        let time = datetime(1994, 7, 15, 13, 45, 31, 42);
+        assert_eq!(time.to_unix(), 774_279_931);
+        assert_eq!(DateTime::from_unix(time.to_unix(), 42), Some(time));
+        assert_eq!(DateTime::from_unix(DateTime::UNIX_END - 1, 0), Some(datetime(9999, 12, 31, 23, 59, 59, 0)));
+        assert_eq!(DateTime::from_unix(DateTime::UNIX_END, 0), None);
+    }
+
+    #[test]
+    fn weekday_and_ordinal() {
+        assert_eq!(datetime(1970, 1, 1, 0, 0, 0, 0).weekday(), Weekday::Thursday);
+        assert_eq!(datetime(1980, 1, 1, 0, 0, 0, 0).weekday(), Weekday::Tuesday);
+        assert_eq!(datetime(2107, 12, 31, 0, 0, 0, 0).weekday(), Weekday::Saturday);
+        assert_eq!(datetime(2000, 12, 31, 0, 0, 0, 0).ordinal(), 366);
+        assert_eq!(datetime(2001, 3, 1, 0, 0, 0, 0).ordinal(), 60);
+    }
+
+    #[test]
+    fn from_bcd_decodes_the_clock() {
+        assert_eq!(DateTime::from_bcd(0x19, 0x94, 0x07, 0x15, 0x13, 0x45, 0x31), Some(datetime(1994, 7, 15, 13, 45, 31, 0)));
+        assert_eq!(DateTime::from_bcd(0x20, 0x00, 0x02, 0x29, 0x00, 0x00, 0x00), Some(datetime(2000, 2, 29, 0, 0, 0, 0)));
+        assert_eq!(DateTime::from_bcd(0x19, 0x9A, 0x07, 0x15, 0x13, 0x45, 0x31), None);
+        assert_eq!(DateTime::from_bcd(0x19, 0x94, 0x13, 0x15, 0x13, 0x45, 0x31), None);
+    }
+
+    #[test]
+    fn format_specifiers() {
+        let time = datetime(1994, 7, 5, 13, 4, 9, 7);
+        assert_eq!(time.to_string(), "1994-07-05 13:04:09");
+        assert_eq!(format!("{}", time.format("%F %T.%f")), "1994-07-05 13:04:09.07");
+        assert_eq!(format!("{}", time.format("%A %a %w %j")), "Tuesday Tue 2 186");
+        assert_eq!(format!("{}", time.format("%d %B %b %y")), "05 July Jul 94");
+        assert_eq!(format!("{}", time.format("%I:%M %p, 100%%")), "01:04 PM, 100%");
+        assert_eq!(format!("{}", time.format("%q %")), "%q %");
+        assert_eq!(format!("{}", datetime(2000, 1, 1, 0, 0, 0, 0).format("%I %p")), "12 AM");
+    }
+
+    #[test]
+    fn parse_round_trips_format() {
+        let time = datetime(1994, 7, 5, 13, 4, 9, 7);
+
+        for format in ["%F %T.%f", "%Y-%m-%d %H:%M:%S.%f", "%d %B %Y %I:%M:%S.%f %p", "%b %d %y %T.%f"] {
+            assert_eq!(DateTime::parse(&time.format(format).to_string(), format), Ok(time), "{format}");
+        }
+    }
+
+    #[test]
+    fn parse_defaults_and_boundaries() {
+        assert_eq!(DateTime::parse("13:04", "%H:%M"), Ok(datetime(1980, 1, 1, 13, 4, 0, 0)));
+        assert_eq!(DateTime::parse("69", "%y"), Ok(datetime(2069, 1, 1, 0, 0, 0, 0)));
+        assert_eq!(DateTime::parse("70", "%y"), Ok(datetime(1970, 1, 1, 0, 0, 0, 0)));
+        assert_eq!(DateTime::parse("12 am", "%I %p"), Ok(datetime(1980, 1, 1, 0, 0, 0, 0)));
+        assert_eq!(DateTime::parse("12 PM", "%I %p"), Ok(datetime(1980, 1, 1, 12, 0, 0, 0)));
+    }
+
+    #[test]
+    fn parse_errors() {
+        assert_eq!(DateTime::parse("1994-02-30", "%F"), Err(ParseError::OutOfRange));
+        assert_eq!(DateTime::parse("13 PM", "%I %p"), Err(ParseError::OutOfRange));
+        assert_eq!(DateTime::parse("1969", "%Y"), Err(ParseError::OutOfRange));
+        assert_eq!(DateTime::parse("1969-07-20", "%Y-%m-%d"), Err(ParseError::OutOfRange));
+        assert_eq!(DateTime::parse("1969-07-20", "%F"), Err(ParseError::OutOfRange));
+        assert_eq!(DateTime::parse("94-07-05", "%F"), Err(ParseError::Invalid));
+        assert_eq!(DateTime::parse("1994-07-05 ", "%F"), Err(ParseError::TrailingInput));
+        assert_eq!(DateTime::parse("Tuesday", "%A"), Err(ParseError::InvalidFormat));
+        assert_eq!(DateTime::parse("1994", "%Y%"), Err(ParseError::InvalidFormat));
+    }
+
+    #[test]
+    fn from_str_accepts_iso_forms() {
+        assert_eq!("1994-07-05".parse(), Ok(datetime(1994, 7, 5, 0, 0, 0, 0)));
+        assert_eq!("1994-07-05 13:04".parse(), Ok(datetime(1994, 7, 5, 13, 4, 0, 0)));
+        assert_eq!("1994-07-05T13:04:09".parse(), Ok(datetime(1994, 7, 5, 13, 4, 9, 0)));
+        assert_eq!("1994-07-05T13:04:09.07".parse(), Ok(datetime(1994, 7, 5, 13, 4, 9, 7)));
+        assert_eq!("1994-07-32".parse::<DateTime>(), Err(ParseError::OutOfRange));
+        assert!("yesterday".parse::<DateTime>().is_err());
+    }
+
+    #[test]
+    fn file_time_conversions() {
+        let time = datetime(2107, 12, 31, 23, 59, 59, 0);
+        let file_time = FileTime::try_from(time).unwrap();
+        assert_eq!(DateTime::from(file_time), datetime(2107, 12, 31, 23, 59, 58, 0));
+        assert_eq!(FileTime::try_from(datetime(1979, 12, 31, 0, 0, 0, 0)), Err(RangeError));
+        assert_eq!(FileTime::try_from(datetime(2108, 1, 1, 0, 0, 0, 0)), Err(RangeError));
+        assert_eq!(DateTime::from(FileTime::from_raw(0, 0)), datetime(1980, 1, 1, 0, 0, 0, 0));
+    }
+}
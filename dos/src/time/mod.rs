@@ -0,0 +1,506 @@
+use crate::process::add_exit_hook;
+use crate::{Error, int21};
+use bios::Registers;
+use bios::pit::Snapshot;
+use bios::rtc::TICKS_PER_DAY;
+use core::ops::{Add, AddAssign, Sub, SubAssign};
+use core::time::Duration;
+
+mod datetime;
+
+pub use datetime::{DateTime, Format, ParseError, RangeError, Weekday};
+
+/// The last clock count returned, so [`Instant::now`] never goes backwards when the clock is set.
+static mut LAST: u64 = 0;
+
+/// Whether an exit hook restores the interval timer, see [`claim_timer`].
+static mut RESTORE_HOOKED: bool = false;
+
+/// A measurement of a monotonically nondecreasing clock.
+/// Opaque and useful only with [`Duration`].
+///
+/// [`Instant`]s are always guaranteed, barring [platform bugs], to be no less than any previously
+/// measured [`Instant`] when created, and are often useful for tasks such as measuring
+/// benchmarks or timing how long an operation takes.
+///
+/// Note, however, that [`Instant`]s are **not** guaranteed to be **steady**. In other
+/// words, each tick of the underlying clock might not be the same length (e.g.
+/// some seconds may be longer than others). An [`Instant`] may jump forwards or
+/// experience time dilation (slow down or speed up), but it will never go
+/// backwards.
+///
+/// [`Instant`]s are opaque types that can only be compared to one another. There is
+/// no method to get "the number of seconds" from an [`Instant`]. Instead, it only
+/// allows measuring the [`Duration`] between two instants (or comparing two [`Instant`]s).
+///
+/// [platform bugs]: Instant#monotonicity
+///
+/// # Monotonicity
+///
+/// [`Instant`] measures time using the BIOS tick counter, which increments roughly every 55 ms
+/// and wraps at midnight, refined by the count of the interval timer that drives it, which
+/// runs at 1.193182 MHz. To read that count, the first call to [`now`] switches the timer to
+/// another mode, which is undone on exit. [`now`] combines them with the DOS date and the BIOS
+/// midnight flag, so it keeps counting across any number of midnights between calls. Setting the clock backwards
+/// makes [`now`] stall until the clock catches up, rather than go back.
+/// In practice such guarantees are – under rare circumstances – broken by hardware, virtualization
+/// or operating system bugs. To work around these bugs and platforms not offering monotonic clocks
+/// [`duration_since`], [`elapsed`] and [`sub`] saturate to zero.
+/// [`checked_duration_since`] can be used to detect and handle situations
+/// where monotonicity is violated, or [`Instant`]s are subtracted in the wrong order.
+///
+/// [`Instant`] counts timer input clocks since 1970-01-01 in a [`u64`], which would overflow
+/// after roughly 490 000 years and break monotonicity.
+///
+/// [`now`]: Instant::now
+/// [`duration_since`]: Instant::duration_since
+/// [`elapsed`]: Instant::elapsed
+/// [`sub`]: Instant::sub
+/// [`checked_duration_since`]: Instant::checked_duration_since
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Instant {
+    /// Interval timer input clocks since 1970-01-01.
+    clocks: u64,
+}
+
+impl Instant {
+    /// Returns an [`Instant`] corresponding to “[`now`]”.
+    ///
+    /// [`now`]: Instant::now
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use dos_std::time::Instant;
+    ///
+    /// let now = Instant::now();
+    /// ```
+    #[must_use]
+    pub fn now() -> Self {
+        // Without a readable count, the time is only as fine as the BIOS tick.
+        let readable = claim_timer();
+
+        // Asking DOS for the date makes it consume a pending midnight flag first, so a flag
+        // still set afterwards means midnight passed after the date was read.
+        let mut regs = Registers { ax: 0x2A00, ..Registers::new() };
+        unsafe { bios::int::<0x21>(&mut regs) };
+
+        let snapshot = if readable {
+            bios::pit::snapshot()
+        } else {
+            let (ticks, midnight) = bios::rtc::peek_ticks();
+            Snapshot { ticks, midnight, elapsed: 0 }
+        };
+
+        let clocks = clocks_at(regs.cx, regs.dh(), regs.dl(), snapshot).max(unsafe { LAST });
+        unsafe { LAST = clocks };
+
+        Self { clocks }
+    }
+
+    /// Returns the amount of time elapsed from another instant to this one,
+    /// or zero duration if that instant is later than this one.
+    #[must_use]
+    pub fn duration_since(&self, earlier: Self) -> Duration {
+        self.checked_duration_since(earlier).unwrap_or_default()
+    }
+
+    /// Returns the amount of time elapsed from another instant to this one,
+    /// or None if that instant is later than this one.
+    ///
+    /// Due to [monotonicity bugs], even under correct logical ordering of the passed [`Instant`]s,
+    /// this method can return [`None`].
+    ///
+    /// [monotonicity bugs]: Instant#monotonicity
+    #[must_use]
+    pub fn checked_duration_since(&self, earlier: Self) -> Option<Duration> {
+        self.clocks.checked_sub(earlier.clocks).map(duration_from_clocks)
+    }
+
+    /// Returns the amount of time elapsed from another [`Instant`] to this one,
+    /// or zero [`Duration`] if that [`Instant`] is later than this one.
+    #[must_use]
+    pub fn saturating_duration_since(&self, earlier: Self) -> Duration {
+        self.checked_duration_since(earlier).unwrap_or_default()
+    }
+
+    /// Returns the amount of time elapsed since this [`Instant`].
+    #[must_use]
+    pub fn elapsed(&self) -> Duration {
+        Self::now() - *self
+    }
+
+    /// Returns `Some(t)` where `t` is the time `self + duration` if `t` can be represented as
+    /// [`Instant`] (which means it's inside the bounds of the underlying data structure), [`None`]
+    /// otherwise.
+    pub fn checked_add(&self, duration: Duration) -> Option<Self> {
+        self.clocks.checked_add(clocks_from_duration(duration)?).map(|clocks| Self { clocks })
+    }
+
+    /// Returns `Some(t)` where `t` is the time `self - duration` if `t` can be represented as
+    /// `Instant` (which means it's inside the bounds of the underlying data structure), [`None`]
+    /// otherwise.
+    pub fn checked_sub(&self, duration: Duration) -> Option<Self> {
+        self.clocks.checked_sub(clocks_from_duration(duration)?).map(|clocks| Self { clocks })
+    }
+}
+
+/// Switches the interval timer to a mode whose count can be read, unless already done, and
+/// has it switched back on exit. Returns `false` if no exit hook is left for that.
+fn claim_timer() -> bool {
+    if bios::pit::divisor().is_some() {
+        return true;
+    }
+
+    // A timer uninstalled since then restores the mode as well, but the hook stays.
+    if !unsafe { RESTORE_HOOKED } {
+        if add_exit_hook(bios::pit::restore).is_none() {
+            return false;
+        }
+
+        unsafe { RESTORE_HOOKED = true };
+    }
+
+    bios::pit::set_divisor(0);
+    true
+}
+
+/// Returns the interval timer input clocks since 1970-01-01 at `snapshot`, taken on the given
+/// DOS date. A set midnight flag means the date is one day behind.
+const fn clocks_at(year: u16, month: u8, day: u8, snapshot: Snapshot) -> u64 {
+    let days = days_from_civil(year, month, day) as u64 + snapshot.midnight as u64;
+    let ticks = days * TICKS_PER_DAY as u64 + snapshot.ticks as u64;
+    ticks << 16 | snapshot.elapsed as u64
+}
+
+/// Converts interval timer input clocks to a [`Duration`], rounding down to a nanosecond.
+const fn duration_from_clocks(clocks: u64) -> Duration {
+    let frequency = bios::pit::FREQUENCY as u64;
+    Duration::new(clocks / frequency, ((clocks % frequency) * 1_000_000_000 / frequency) as u32)
+}
+
+/// Converts a [`Duration`] to interval timer input clocks, rounding down, or returns [`None`]
+/// on overflow.
+const fn clocks_from_duration(duration: Duration) -> Option<u64> {
+    let frequency = bios::pit::FREQUENCY as u64;
+    match duration.as_secs().checked_mul(frequency) {
+        Some(clocks) => clocks.checked_add(duration.subsec_nanos() as u64 * frequency / 1_000_000_000),
+        None => None,
+    }
+}
+
+impl Add<Duration> for Instant {
+    type Output = Self;
+
+    /// # Panics
+    ///
+    /// This function may panic if the resulting point in time cannot be represented by the
+    /// underlying data structure. See [`Instant::checked_add`] for a version without panic.
+    fn add(self, rhs: Duration) -> Self::Output {
+        self.checked_add(rhs).expect("overflow when adding duration to instant")
+    }
+}
+
+impl AddAssign<Duration> for Instant {
+    fn add_assign(&mut self, other: Duration) {
+        *self = *self + other;
+    }
+}
+
+impl Sub for Instant {
+    type Output = Duration;
+
+    fn sub(self, rhs: Self) -> Self::Output {
+        self.duration_since(rhs)
+    }
+}
+
+impl Sub<Duration> for Instant {
+    type Output = Instant;
+
+    fn sub(self, rhs: Duration) -> Instant {
+        self.checked_sub(rhs).expect("overflow when subtracting duration from instant")
+    }
+}
+
+impl SubAssign<Duration> for Instant {
+    fn sub_assign(&mut self, other: Duration) {
+        *self = *self - other;
+    }
+}
+
+/// Sets the date of the DOS clock (int 21h, AH=2Bh).
+///
+/// Fails with [`Error::InvalidParameter`] if DOS rejects the date, such as one before 1980.
+pub fn set_date(year: u16, month: u8, day: u8) -> Result<(), Error> {
+    let regs = unsafe { int21(Registers { ax: 0x2B00, cx: year, dx: (month as u16) << 8 | day as u16, ..Registers::new() }) }?;
+    if regs.al() == 0xFF { Err(Error::InvalidParameter) } else { Ok(()) }
+}
+
+/// Sets the time of day of the DOS clock (int 21h, AH=2Dh).
+///
+/// Fails with [`Error::InvalidParameter`] if DOS rejects the time.
+pub fn set_time(hour: u8, minute: u8, second: u8, hundredths: u8) -> Result<(), Error> {
+    let regs = unsafe {
+        int21(Registers { ax: 0x2D00, cx: (hour as u16) << 8 | minute as u16, dx: (second as u16) << 8 | hundredths as u16, ..Registers::new() })
+    }?;
+    if regs.al() == 0xFF { Err(Error::InvalidParameter) } else { Ok(()) }
+}
+
+/// A file timestamp in the packed date and time words DOS keeps in directory entries.
+///
+/// Timestamps range from 1980-01-01 to 2107-12-31 with a granularity of two seconds, and are
+/// in local time, as DOS has no notion of time zones.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct FileTime {
+    date: u16,
+    time: u16,
+}
+
+impl FileTime {
+    /// The earliest representable timestamp, 1980-01-01 00:00:00.
+    pub const EPOCH: Self = Self { date: (1 << 5) | 1, time: 0 };
+
+    /// Seconds from 1970-01-01 to [`FileTime::EPOCH`].
+    const UNIX_OFFSET: u64 = 315_532_800;
+
+    /// Creates a timestamp from the packed words, as returned by DOS.
+    #[inline]
+    #[must_use]
+    pub const fn from_raw(date: u16, time: u16) -> Self {
+        Self { date, time }
+    }
+
+    /// Creates a timestamp from its parts, or returns [`None`] if they do not form a valid
+    /// date and time in the representable range. Odd seconds are rounded down.
+    #[must_use]
+    pub const fn new(year: u16, month: u8, day: u8, hour: u8, minute: u8, second: u8) -> Option<Self> {
+        if year < 1980 || year > 2107 || month < 1 || month > 12 || day < 1 || day > days_in_month(year, month) {
+            return None;
+        }
+
+        if hour > 23 || minute > 59 || second > 59 {
+            return None;
+        }
+
+        Some(Self {
+            date: ((year - 1980) << 9) | ((month as u16) << 5) | day as u16,
+            time: ((hour as u16) << 11) | ((minute as u16) << 5) | (second as u16 / 2),
+        })
+    }
+
+    /// Returns the packed date word: bits 15-9 year since 1980, 8-5 month, 4-0 day.
+    #[inline]
+    #[must_use]
+    pub const fn date(&self) -> u16 {
+        self.date
+    }
+
+    /// Returns the packed time word: bits 15-11 hour, 10-5 minute, 4-0 second divided by two.
+    #[inline]
+    #[must_use]
+    pub const fn time(&self) -> u16 {
+        self.time
+    }
+
+    #[inline]
+    #[must_use]
+    pub const fn year(&self) -> u16 {
+        1980 + (self.date >> 9)
+    }
+
+    #[inline]
+    #[must_use]
+    pub const fn month(&self) -> u8 {
+        (self.date >> 5) as u8 & 0x0F
+    }
+
+    #[inline]
+    #[must_use]
+    pub const fn day(&self) -> u8 {
+        self.date as u8 & 0x1F
+    }
+
+    #[inline]
+    #[must_use]
+    pub const fn hour(&self) -> u8 {
+        (self.time >> 11) as u8
+    }
+
+    #[inline]
+    #[must_use]
+    pub const fn minute(&self) -> u8 {
+        (self.time >> 5) as u8 & 0x3F
+    }
+
+    #[inline]
+    #[must_use]
+    pub const fn second(&self) -> u8 {
+        (self.time as u8 & 0x1F) * 2
+    }
+
+    /// Returns the seconds since 1970-01-01 00:00:00, treating the local time as UTC.
+    ///
+    /// Out of range fields, as found on disks written by broken software, are clamped.
+    #[must_use]
+    pub const fn to_unix(&self) -> u64 {
+        let month = if self.month() < 1 {
+            1
+        } else if self.month() > 12 {
+            12
+        } else {
+            self.month()
+        };
+        let day = if self.day() < 1 { 1 } else { self.day() };
+        let days = days_from_civil(self.year(), month, day) as u64;
+
+        days * 86_400 + self.hour() as u64 * 3600 + self.minute() as u64 * 60 + self.second() as u64
+    }
+
+    /// Returns the timestamp `secs` seconds after 1970-01-01 00:00:00, rounded down to two
+    /// seconds, or [`None`] if it lies outside the representable range.
+    #[must_use]
+    pub const fn from_unix(secs: u64) -> Option<Self> {
+        if secs < Self::UNIX_OFFSET {
+            return None;
+        }
+
+        let (year, month, day) = civil_from_days((secs / 86_400) as u32);
+        let secs = (secs % 86_400) as u32;
+        Self::new(year, month, day, (secs / 3600) as u8, (secs / 60 % 60) as u8, (secs % 60) as u8)
+    }
+}
+
+/// Returns the number of days in `month` of `year`.
+#[must_use]
+pub const fn days_in_month(year: u16, month: u8) -> u8 {
+    match month {
+        2 if year.is_multiple_of(4) && (!year.is_multiple_of(100) || year.is_multiple_of(400)) => 29,
+        2 => 28,
+        4 | 6 | 9 | 11 => 30,
+        _ => 31,
+    }
+}
+
+/// Returns the days since 1970-01-01 for a proleptic Gregorian date no earlier than that.
+#[must_use]
+pub const fn days_from_civil(year: u16, month: u8, day: u8) -> u32 {
+    let year = year as u32 - (month <= 2) as u32;
+    let era = year / 400;
+    let year_of_era = year - era * 400;
+    let month = month as u32;
+    let day_of_year = (153 * if month > 2 { month - 3 } else { month + 9 } + 2) / 5 + day as u32 - 1;
+    let day_of_era = year_of_era * 365 + year_of_era / 4 - year_of_era / 100 + day_of_year;
+
+    era * 146_097 + day_of_era - 719_468
+}
+
+/// Returns the proleptic Gregorian date `days` days after 1970-01-01, as year, month and day.
+#[must_use]
+pub const fn civil_from_days(days: u32) -> (u16, u8, u8) {
+    let days = days + 719_468;
+    let era = days / 146_097;
+    let day_of_era = days - era * 146_097;
+    let year_of_era = (day_of_era - day_of_era / 1460 + day_of_era / 36_524 - day_of_era / 146_096) / 365;
+    let day_of_year = day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+    let month = (5 * day_of_year + 2) / 153;
+    let day = day_of_year - (153 * month + 2) / 5 + 1;
+    let month = if month < 10 { month + 3 } else { month - 9 };
+    let year = year_of_era + era * 400 + (month <= 2) as u32;
+
+    (year as u16, month as u8, day as u8)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn file_time_packs_its_fields() {
+        let time = FileTime::new(1994, 7, 15, 13, 45, 31).unwrap();
+        assert_eq!(time.date(), (14 << 9) | (7 << 5) | 15);
+        assert_eq!(time.time(), (13 << 11) | (45 << 5) | 15);
+        assert_eq!(FileTime::from_raw(time.date(), time.time()), time);
+        assert_eq!((time.year(), time.month(), time.day()), (1994, 7, 15));
+        assert_eq!((time.hour(), time.minute(), time.second()), (13, 45, 30));
+    }
+
+    #[test]
+    fn file_time_range() {
+        let first = FileTime::new(1980, 1, 1, 0, 0, 0).unwrap();
+        assert_eq!(first, FileTime::EPOCH);
+        assert_eq!(first.to_unix(), FileTime::UNIX_OFFSET);
+
+        let last = FileTime::new(2107, 12, 31, 23, 59, 59).unwrap();
+        assert_eq!((last.year(), last.month(), last.day()), (2107, 12, 31));
+        assert_eq!((last.hour(), last.minute(), last.second()), (23, 59, 58));
+
+        assert_eq!(FileTime::new(1979, 12, 31, 23, 59, 59), None);
+        assert_eq!(FileTime::new(2108, 1, 1, 0, 0, 0), None);
+        assert_eq!(FileTime::new(2001, 2, 29, 0, 0, 0), None);
+        assert_eq!(FileTime::new(2000, 1, 1, 24, 0, 0), None);
+    }
+
+    #[test]
+    fn file_time_unix_round_trip() {
+        for time in [FileTime::EPOCH, FileTime::new(2000, 2, 29, 12, 0, 0).unwrap(), FileTime::new(2107, 12, 31, 23, 59, 58).unwrap()] {
+            assert_eq!(FileTime::from_unix(time.to_unix()), Some(time));
+        }
+
+        assert_eq!(FileTime::from_unix(FileTime::UNIX_OFFSET - 1), None);
+        assert_eq!(FileTime::from_unix(FileTime::new(2107, 12, 31, 23, 59, 58).unwrap().to_unix() + 2), None);
+        assert_eq!(FileTime::from_unix(FileTime::UNIX_OFFSET + 3), FileTime::new(1980, 1, 1, 0, 0, 2));
+    }
+
+    #[test]
+    fn file_time_clamps_broken_fields() {
+        assert_eq!(FileTime::from_raw(0, 0).to_unix(), FileTime::UNIX_OFFSET);
+        assert_eq!(FileTime::from_raw(15 << 5 | 1, 0).to_unix(), FileTime::new(1980, 12, 1, 0, 0, 0).unwrap().to_unix());
+    }
+
+    #[test]
+    fn clocks_count_across_midnight() {
+        let snapshot = |ticks, midnight, elapsed| Snapshot { ticks, midnight, elapsed };
+        let day = TICKS_PER_DAY as u64 * 65_536;
+
+        assert_eq!(clocks_at(1970, 1, 1, snapshot(0, false, 0)), 0);
+        assert_eq!(clocks_at(1970, 1, 1, snapshot(1, false, 5)), 65_536 + 5);
+        assert_eq!(clocks_at(1970, 1, 2, snapshot(0, false, 0)), day);
+
+        // The last tick of a day and the first one of the next, before DOS saw the midnight flag.
+        let before = clocks_at(1994, 7, 15, snapshot(TICKS_PER_DAY - 1, false, 65_535));
+        let after = clocks_at(1994, 7, 15, snapshot(0, true, 0));
+        assert_eq!(after - before, 1);
+        assert_eq!(after, clocks_at(1994, 7, 16, snapshot(0, false, 0)));
+    }
+
+    #[test]
+    fn days_in_month_follows_leap_years() {
+        assert_eq!(days_in_month(1996, 2), 29);
+        assert_eq!(days_in_month(1900, 2), 28);
+        assert_eq!(days_in_month(2000, 2), 29);
+        assert_eq!(days_in_month(2100, 2), 28);
+        assert_eq!(days_in_month(1999, 4), 30);
+        assert_eq!(days_in_month(1999, 12), 31);
+    }
+
+    #[test]
+    fn civil_days_round_trip() {
+        assert_eq!(days_from_civil(1970, 1, 1), 0);
+        assert_eq!(days_from_civil(1980, 1, 1), 3652);
+        assert_eq!(days_from_civil(2000, 3, 1), 11_017);
+        assert_eq!(civil_from_days(0), (1970, 1, 1));
+        assert_eq!(civil_from_days(11_016), (2000, 2, 29));
+
+        let mut days = days_from_civil(1970, 1, 1);
+        for year in 1970..=2108 {
+            for month in 1..=12 {
+                for day in 1..=days_in_month(year, month) {
+                    assert_eq!(days_from_civil(year, month, day), days);
+                    assert_eq!(civil_from_days(days), (year, month, day));
+                    days += 1;
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,183 @@
+use crate::interrupt::{Action, InterruptFrame, InterruptGuard};
+use crate::process::{add_exit_hook, remove_exit_hook};
+use bios::cpu::{outb, without_interrupts};
+use bios::pit::{self, FREQUENCY};
+
+/// Callbacks a [`Timer`] can run at once.
+const MAX_CALLBACKS: usize = 8;
+
+#[derive(Clone, Copy)]
+struct Callback {
+    callback: fn(),
+    /// Timer interrupts between two calls.
+    period: u32,
+    /// Timer interrupts left until the next call.
+    countdown: u32,
+}
+
+static mut CALLBACKS: [Option<Callback>; MAX_CALLBACKS] = [None; MAX_CALLBACKS];
+
+/// Whether the handler is hooked, as only one [`Timer`] can exist at a time.
+static mut INSTALLED: bool = false;
+
+/// Keeps [`HANDLER`] installed, taken by [`uninstall`].
+static mut GUARD: Option<InterruptGuard> = None;
+
+crate::interrupt_handler! {
+    /// The int 08h handler, on a private stack of 1 KiB.
+    static HANDLER = on_interrupt, stack = 1024;
+}
+
+/// An error returned when a [`Timer`] cannot be set up.
+#[derive(thiserror::Error, Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum TimerError {
+    /// Another [`Timer`] is already installed.
+    #[error("timer already installed")]
+    AlreadyInstalled,
+    /// The frequency or rate is outside the supported range.
+    #[error("frequency out of range")]
+    InvalidFrequency,
+    /// All callback or exit hook slots are taken.
+    #[error("no free slot")]
+    NoFreeSlot,
+}
+
+/// Identifies a callback added to a [`Timer`], see [`Timer::remove`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct CallbackId(usize);
+
+/// The timer interrupt (int 08h), sped up to run callbacks at configurable rates.
+///
+/// The interval timer is reprogrammed to the requested frequency, and the interrupt hooked
+/// with a handler that runs the callbacks, then passes on to the original handler at the
+/// original rate of about 18.2 Hz. The BIOS tick count, and with it the DOS clock, int 1Ch and
+/// [`Instant`], keep running at the right speed.
+///
+/// Callbacks run inside the interrupt handler with interrupts disabled, on a private stack of
+/// 1 KiB. They must be short and must not call DOS.
+///
+/// Dropping the timer, or exiting through [`process::exit`] or by returning from `main`,
+/// restores the interval timer and the interrupt vector.
+///
+/// [`Instant`]: crate::time::Instant
+/// [`process::exit`]: crate::process::exit
+#[derive(Debug)]
+pub struct Timer {
+    divisor: u16,
+    exit_hook: usize,
+}
+
+impl Timer {
+    /// The lowest supported frequency, the rate the BIOS runs the timer at.
+    pub const MIN_FREQUENCY: u32 = 19;
+    /// The highest supported frequency. Faster rates leave slow machines no time for anything else.
+    pub const MAX_FREQUENCY: u32 = 100_000;
+
+    /// Hooks the timer interrupt and runs the interval timer at `frequency` Hz, rounded to the
+    /// nearest rate the hardware supports.
+    pub fn new(frequency: u32) -> Result<Self, TimerError> {
+        if !(Self::MIN_FREQUENCY..=Self::MAX_FREQUENCY).contains(&frequency) {
+            return Err(TimerError::InvalidFrequency);
+        }
+
+        if unsafe { INSTALLED } {
+            return Err(TimerError::AlreadyInstalled);
+        }
+
+        let exit_hook = add_exit_hook(uninstall).ok_or(TimerError::NoFreeSlot)?;
+        let divisor = ((FREQUENCY + frequency / 2) / frequency).min(0xFFFF) as u16;
+
+        without_interrupts(|| {
+            unsafe {
+                GUARD = Some(InterruptGuard::install(0x08, &HANDLER));
+                INSTALLED = true;
+            }
+
+            pit::set_divisor(divisor);
+        });
+
+        Ok(Self { divisor, exit_hook })
+    }
+
+    /// Returns the frequency the interval timer actually runs at, in Hz.
+    #[inline]
+    #[must_use]
+    pub const fn frequency(&self) -> u32 {
+        FREQUENCY / self.divisor as u32
+    }
+
+    /// Adds `callback` to run `rate` times per second, rounded to a whole number of timer
+    /// interrupts. The rate must not exceed the [`frequency`] of the timer.
+    ///
+    /// [`frequency`]: Timer::frequency
+    pub fn add(&mut self, rate: u32, callback: fn()) -> Result<CallbackId, TimerError> {
+        if rate == 0 || rate > self.frequency() {
+            return Err(TimerError::InvalidFrequency);
+        }
+
+        let period = (self.frequency() + rate / 2) / rate;
+
+        without_interrupts(|| {
+            let slot = unsafe { CALLBACKS }.iter().position(Option::is_none).ok_or(TimerError::NoFreeSlot)?;
+            unsafe { CALLBACKS[slot] = Some(Callback { callback, period, countdown: period }) };
+            Ok(CallbackId(slot))
+        })
+    }
+
+    /// Removes a callback added with [`Timer::add`]. It is not called again once this returns.
+    pub fn remove(&mut self, id: CallbackId) {
+        without_interrupts(|| unsafe { CALLBACKS[id.0] = None });
+    }
+}
+
+impl Drop for Timer {
+    fn drop(&mut self) {
+        remove_exit_hook(self.exit_hook);
+        uninstall();
+    }
+}
+
+/// Restores the interval timer and the interrupt vector, and drops all callbacks.
+fn uninstall() {
+    without_interrupts(|| {
+        if !unsafe { INSTALLED } {
+            return;
+        }
+
+        pit::restore();
+        drop(unsafe { core::ptr::replace(&raw mut GUARD, None) });
+
+        unsafe {
+            CALLBACKS = [None; MAX_CALLBACKS];
+            INSTALLED = false;
+        }
+    });
+}
+
+/// Runs the callbacks that are due, then decides whether the BIOS gets this interrupt.
+// Indexing keeps the callbacks in place, as a callback may remove itself or others.
+#[allow(clippy::needless_range_loop)]
+extern "C" fn on_interrupt(_frame: &mut InterruptFrame) -> Action {
+    for slot in 0..MAX_CALLBACKS {
+        let Some(mut callback) = (unsafe { CALLBACKS[slot] }) else { continue };
+        callback.countdown -= 1;
+
+        if callback.countdown == 0 {
+            callback.countdown = callback.period;
+        }
+
+        unsafe { CALLBACKS[slot] = Some(callback) };
+
+        if callback.countdown == callback.period {
+            (callback.callback)();
+        }
+    }
+
+    // The BIOS acknowledges the interrupts it gets, the rest are acknowledged here.
+    if unsafe { pit::advance_phase() } {
+        Action::Chain
+    } else {
+        unsafe { outb(0x20, 0x20) };
+        Action::Return
+    }
+}
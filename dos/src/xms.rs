@@ -0,0 +1,339 @@
+use crate::FarPtr;
+use crate::error::error_codes;
+use bios::Registers;
+use core::arch::asm;
+
+pub type Result<T> = core::result::Result<T, XmsError>;
+
+error_codes! {
+    /// An error code returned by the XMS driver.
+    pub enum XmsError {
+        NotImplemented = 0x80 => "Function not implemented, or no XMS driver installed.",
+        VdiskDetected = 0x81 => "VDISK device detected.",
+        A20Error = 0x82 => "A20 error.",
+        DriverError = 0x8E => "General driver error.",
+        UnrecoverableError = 0x8F => "Unrecoverable driver error.",
+        NoHma = 0x90 => "HMA does not exist.",
+        HmaInUse = 0x91 => "HMA already in use.",
+        HmaTooSmall = 0x92 => "Request smaller than /HMAMIN.",
+        HmaNotAllocated = 0x93 => "HMA not allocated.",
+        A20StillEnabled = 0x94 => "A20 line still enabled.",
+        OutOfMemory = 0xA0 => "All extended memory is allocated.",
+        OutOfHandles = 0xA1 => "All extended memory handles are in use.",
+        InvalidHandle = 0xA2 => "Invalid handle.",
+        InvalidSourceHandle = 0xA3 => "Invalid source handle.",
+        InvalidSourceOffset = 0xA4 => "Invalid source offset.",
+        InvalidDestinationHandle = 0xA5 => "Invalid destination handle.",
+        InvalidDestinationOffset = 0xA6 => "Invalid destination offset.",
+        InvalidLength = 0xA7 => "Invalid length.",
+        InvalidOverlap = 0xA8 => "Invalid overlap in move.",
+        ParityError = 0xA9 => "Parity error.",
+        NotLocked = 0xAA => "Block not locked.",
+        Locked = 0xAB => "Block locked.",
+        LockCountOverflow = 0xAC => "Lock count overflow.",
+        LockFailed = 0xAD => "Lock failed.",
+        SmallerUmbAvailable = 0xB0 => "Only a smaller UMB available.",
+        NoUmbAvailable = 0xB1 => "No UMB available.",
+        InvalidUmbSegment = 0xB2 => "Invalid UMB segment.",
+    }
+}
+
+/// The entry point of the XMS driver as offset and segment for `lcall`, or zero if not
+/// looked up yet.
+static mut ENTRY: [u16; 2] = [0; 2];
+
+/// The version of the XMS specification a driver implements, see [`version`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Version {
+    pub major: u8,
+    pub minor: u8,
+}
+
+/// The free extended memory in KiB, see [`free_memory`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct FreeMemory {
+    /// The largest block that can be allocated.
+    pub largest: u16,
+    /// All free extended memory.
+    pub total: u16,
+}
+
+/// A move between two places in memory (XMS function 0Bh). Handle 0 stands for conventional
+/// memory, addressed by a segment and offset packed like a far pointer.
+#[repr(C, packed)]
+struct ExtendedMove {
+    length: u32,
+    source_handle: u16,
+    source_offset: u32,
+    destination_handle: u16,
+    destination_offset: u32,
+}
+
+/// Returns `true` if an XMS driver such as HIMEM is installed (int 2Fh, AX=4300h).
+#[must_use]
+pub fn is_installed() -> bool {
+    let mut regs = Registers { ax: 0x4300, ..Registers::new() };
+    unsafe { bios::int::<0x2F>(&mut regs) };
+    regs.al() == 0x80
+}
+
+/// Looks up the entry point of the driver (int 2Fh, AX=4310h) on first use.
+fn entry() -> Result<()> {
+    if unsafe { ENTRY } != [0; 2] {
+        return Ok(());
+    }
+
+    if !is_installed() {
+        return Err(XmsError::NotImplemented);
+    }
+
+    let mut regs = Registers { ax: 0x4310, ..Registers::new() };
+    unsafe { bios::int::<0x2F>(&mut regs) };
+    unsafe { ENTRY = [regs.bx, regs.es] };
+    Ok(())
+}
+
+/// Calls XMS function `function` with BX, DX and SI, returning AX, BX and DX. A result of 0
+/// in AX is turned into the error in BL.
+fn call(function: u8, bx: u16, dx: u16, si: u16) -> Result<(u16, u16, u16)> {
+    entry()?;
+
+    let (mut ax, mut bx, mut dx) = ((function as u16) << 8, bx, dx);
+
+    unsafe {
+        asm!(
+            // LLVM reserves ESI, so it is saved here instead of being an operand.
+            "push esi",
+            "mov si, {si:x}",
+            "lcall cs:[{entry}]",
+            "pop esi",
+            entry = sym ENTRY,
+            si = in(reg) si,
+            inout("ax") ax,
+            inout("bx") bx,
+            inout("dx") dx,
+            lateout("cx") _,
+        );
+    }
+
+    if ax == 0 { Err(XmsError::from(bx as u8)) } else { Ok((ax, bx, dx)) }
+}
+
+/// Returns the version of the XMS specification the driver implements (XMS function 00h).
+pub fn version() -> Result<Version> {
+    // Function 00h returns the version in BCD and does not fail.
+    let (version, _, _) = call(0x00, 0, 0, 0)?;
+    let major = bios::rtc::from_bcd((version >> 8) as u8).ok_or(XmsError::DriverError)?;
+    let minor = bios::rtc::from_bcd(version as u8).ok_or(XmsError::DriverError)?;
+    Ok(Version { major, minor })
+}
+
+/// Returns the free extended memory (XMS function 08h).
+pub fn free_memory() -> Result<FreeMemory> {
+    // With nothing free, AX is 0 and BL is A0h, which is no error here.
+    match call(0x08, 0, 0, 0) {
+        Ok((largest, _, total)) => Ok(FreeMemory { largest, total }),
+        Err(XmsError::OutOfMemory) => Ok(FreeMemory { largest: 0, total: 0 }),
+        Err(error) => Err(error),
+    }
+}
+
+/// Moves `length` bytes, which must be even, between two places given as handle and offset.
+fn move_memory(length: u32, source: (u16, u32), destination: (u16, u32)) -> Result<()> {
+    let descriptor = ExtendedMove {
+        length,
+        source_handle: source.0,
+        source_offset: source.1,
+        destination_handle: destination.0,
+        destination_offset: destination.1,
+    };
+
+    call(0x0B, 0, 0, &raw const descriptor as u16).map(|_| ())
+}
+
+/// Returns `pointer` as an offset for handle 0, the segment in the high word.
+#[inline]
+fn conventional(pointer: *const u8) -> u32 {
+    let pointer = FarPtr::from_ptr(pointer);
+    (pointer.segment() as u32) << 16 | pointer.offset() as u32
+}
+
+/// An extended memory block (EMB), freed when dropped.
+///
+/// Extended memory lies above 1 MiB and cannot be addressed in real mode. Its contents are
+/// copied in and out with [`read`] and [`write`], which go through the driver.
+///
+/// [`read`]: XmsBlock::read
+/// [`write`]: XmsBlock::write
+#[derive(Debug)]
+pub struct XmsBlock {
+    handle: u16,
+    /// The size in KiB.
+    size: u16,
+}
+
+impl XmsBlock {
+    /// Allocates a block of `kib` KiB (XMS function 09h).
+    pub fn new(kib: u16) -> Result<Self> {
+        let (_, _, handle) = call(0x09, 0, kib, 0)?;
+        Ok(Self { handle, size: kib })
+    }
+
+    /// Returns the handle of the block.
+    #[inline]
+    #[must_use]
+    pub const fn handle(&self) -> u16 {
+        self.handle
+    }
+
+    /// Returns the size of the block in bytes.
+    #[inline]
+    #[must_use]
+    pub const fn len(&self) -> u32 {
+        self.size as u32 * 1024
+    }
+
+    /// Returns `true` if the block is empty.
+    #[inline]
+    #[must_use]
+    pub const fn is_empty(&self) -> bool {
+        self.size == 0
+    }
+
+    /// Resizes the block to `kib` KiB (XMS function 0Fh). Fails if the block is locked.
+    pub fn resize(&mut self, kib: u16) -> Result<()> {
+        call(0x0F, kib, self.handle, 0)?;
+        self.size = kib;
+        Ok(())
+    }
+
+    /// Locks the block (XMS function 0Ch), so that it does not move and its physical address
+    /// can be handed to code running in protected mode or to a DMA controller.
+    pub fn lock(&mut self) -> Result<XmsLock<'_>> {
+        let (_, low, high) = call(0x0C, 0, self.handle, 0)?;
+        Ok(XmsLock { block: self, address: (high as u32) << 16 | low as u32 })
+    }
+
+    /// Copies the bytes at `offset` into `buf` (XMS function 0Bh).
+    pub fn read(&self, offset: u32, buf: &mut [u8]) -> Result<()> {
+        let even = self.check(offset, buf.len())? & !1;
+
+        if even > 0 {
+            move_memory(even, (self.handle, offset), (0, conventional(buf.as_mut_ptr())))?;
+        }
+
+        // The driver only moves words, so an odd last byte comes with a neighbour.
+        if let Some(last) = buf.get_mut(even as usize) {
+            let (at, index) = pair(offset + even, self.len());
+            let mut pair = [0u8; 2];
+            move_memory(2, (self.handle, at), (0, conventional(pair.as_mut_ptr())))?;
+            *last = pair[index];
+        }
+
+        Ok(())
+    }
+
+    /// Copies `buf` to the bytes at `offset` (XMS function 0Bh).
+    pub fn write(&mut self, offset: u32, buf: &[u8]) -> Result<()> {
+        let even = self.check(offset, buf.len())? & !1;
+
+        if even > 0 {
+            move_memory(even, (0, conventional(buf.as_ptr())), (self.handle, offset))?;
+        }
+
+        // The driver only moves words, so an odd last byte is merged into its neighbour.
+        if let Some(&last) = buf.get(even as usize) {
+            let (at, index) = pair(offset + even, self.len());
+            let mut pair = [0u8; 2];
+            move_memory(2, (self.handle, at), (0, conventional(pair.as_mut_ptr())))?;
+            pair[index] = last;
+            move_memory(2, (0, conventional(pair.as_ptr())), (self.handle, at))?;
+        }
+
+        Ok(())
+    }
+
+    /// Copies `length` bytes, which must be even, from `source` at `source_offset` to `self`
+    /// at `offset` (XMS function 0Bh).
+    pub fn copy_from(&mut self, offset: u32, source: &XmsBlock, source_offset: u32, length: u32) -> Result<()> {
+        if !length.is_multiple_of(2) {
+            return Err(XmsError::InvalidLength);
+        }
+
+        self.check(offset, length as usize)?;
+        source.check(source_offset, length as usize)?;
+        move_memory(length, (source.handle, source_offset), (self.handle, offset))
+    }
+
+    /// Returns `length` as `u32` if `length` bytes at `offset` lie within the block.
+    fn check(&self, offset: u32, length: usize) -> Result<u32> {
+        let length = u32::try_from(length).map_err(|_| XmsError::InvalidLength)?;
+
+        match offset.checked_add(length) {
+            Some(end) if end <= self.len() => Ok(length),
+            _ => Err(XmsError::InvalidLength),
+        }
+    }
+}
+
+/// Returns the offset of a pair of bytes within a block of `len` bytes containing the byte at
+/// `offset`, and the index of that byte in the pair.
+#[inline]
+const fn pair(offset: u32, len: u32) -> (u32, usize) {
+    if offset + 2 <= len { (offset, 0) } else { (offset - 1, 1) }
+}
+
+impl Drop for XmsBlock {
+    fn drop(&mut self) {
+        // Function 0Ah fails on a locked block, which the borrow of any XmsLock rules out.
+        let _ = call(0x0A, 0, self.handle, 0);
+    }
+}
+
+/// A locked [`XmsBlock`], unlocked when dropped.
+#[derive(Debug)]
+pub struct XmsLock<'a> {
+    block: &'a XmsBlock,
+    address: u32,
+}
+
+impl XmsLock<'_> {
+    /// Returns the 32-bit physical address of the block.
+    #[inline]
+    #[must_use]
+    pub const fn address(&self) -> u32 {
+        self.address
+    }
+}
+
+impl Drop for XmsLock<'_> {
+    fn drop(&mut self) {
+        let _ = call(0x0D, 0, self.block.handle, 0);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use core::mem::ManuallyDrop;
+
+    #[test]
+    fn pair_stays_within_the_block() {
+        assert_eq!(pair(0, 1024), (0, 0));
+        assert_eq!(pair(1021, 1024), (1021, 0));
+        assert_eq!(pair(1022, 1024), (1022, 0));
+        assert_eq!(pair(1023, 1024), (1022, 1));
+    }
+
+    #[test]
+    fn check_bounds() {
+        // Never dropped, as that would call the driver.
+        let block = ManuallyDrop::new(XmsBlock { handle: 1, size: 1 });
+
+        assert_eq!(block.check(0, 1024), Ok(1024));
+        assert_eq!(block.check(1023, 1), Ok(1));
+        assert_eq!(block.check(1023, 0), Ok(0));
+        assert_eq!(block.check(1023, 2), Err(XmsError::InvalidLength));
+        assert_eq!(block.check(u32::MAX, 1), Err(XmsError::InvalidLength));
+    }
+}
@@ -1,15 +1,19 @@
 #![no_std]
 #![feature(linkage)]
 
-use core::panic::PanicInfo;
+use bios::Registers;
 
 unsafe extern "C" {
     static __bss_start: *mut u8;
     static __bss_size: usize;
+    static __stack_start: u8;
+    static __stack_end: u8;
 
+    #[cfg(not(test))]
     safe fn main();
 }
 
+#[cfg(not(test))]
 #[unsafe(naked)]
 #[unsafe(no_mangle)]
 // #[unsafe(link_section = ".rt")]
@@ -21,20 +25,139 @@ extern "C" fn _start() -> ! {
         "shr ecx, 2",
         "xor eax, eax",
         "rep stosd",
+        // Size the memory block and move the stack to where that puts it.
+        "call {init_memory}",
+        "mov esp, eax",
         // Call main.
         "call {main}",
+        // Undo whatever the program hooked.
+        "call {exit_hooks}",
         // Exit to DOS.
         "mov ax, 0x4C00",
         "int 0x21",
         __bss_start = sym __bss_start,
         __bss_size = sym __bss_size,
+        init_memory = sym init_memory,
         main = sym main,
+        exit_hooks = sym __dos_exit_hooks,
     };
 }
 
+/// Whether the heap lives in the segment of the program, as with the `near_heap` feature of
+/// `std`, which replaces this.
+#[unsafe(no_mangle)]
+#[linkage = "weak"]
+static __dos_near_heap: bool = false;
+
+/// Whether the heap is made of DOS memory blocks, as with the default allocator of `std`, which
+/// replaces this.
+#[unsafe(no_mangle)]
+#[linkage = "weak"]
+static __dos_block_heap: bool = false;
+
+/// The memory left for the heap at startup. With a near heap it is the heap itself, otherwise
+/// DOS allocates blocks from it.
+#[unsafe(no_mangle)]
+static mut __dos_heap_start: u32 = 0;
+#[unsafe(no_mangle)]
+static mut __dos_heap_end: u32 = 0;
+
+/// The highest the stack can start. Calls and pushes only move SP, which wraps within the
+/// segment, but the code addresses the stack through ESP, so starting at 0x10000 would put
+/// every access past the end of the segment.
+const STACK_LIMIT: u32 = 0xFFFE;
+
+/// Shrinks the memory block of the program (int 21h, AH=4Ah) and lays out the stack and the
+/// heap, returning the top of the stack.
+#[cfg_attr(test, allow(dead_code))]
+extern "C" fn init_memory() -> u32 {
+    let stack_start = &raw const __stack_start as u32;
+    let stack_end = &raw const __stack_end as u32;
+
+    // DOS hands a `.COM` program all the memory it has. Keep at most the segment.
+    let end = match resize(0x1000) {
+        Ok(()) => 0x10000,
+        Err(available) => available as u32 * 16,
+    };
+
+    let (heap_start, heap_end, stack_top) = layout(end, stack_start, stack_end, __dos_near_heap, __dos_block_heap);
+
+    if __dos_block_heap {
+        let _ = resize(stack_end.div_ceil(16) as u16);
+    }
+
+    unsafe {
+        __dos_heap_start = heap_start;
+        __dos_heap_end = heap_end;
+    }
+
+    stack_top
+}
+
+/// Returns the start and end of the heap and the top of the stack, for a program whose memory
+/// block ends at `end` and whose `.stack` section spans `stack_start..stack_end`.
+///
+/// The stack stays at the top of the segment, as DOS sets it up, unless the heap is made of DOS
+/// memory blocks. Those have to land in the segment, so the stack then moves right after bss,
+/// with the size of the `.stack` section, and the rest of the segment goes back to DOS.
+fn layout(end: u32, stack_start: u32, stack_end: u32, near_heap: bool, block_heap: bool) -> (u32, u32, u32) {
+    if near_heap {
+        // The heap gets the room between bss and the stack, less the size of the stack.
+        let stack_size = stack_end - stack_start;
+        let heap_end = end.saturating_sub(stack_size);
+        (stack_start, heap_end.max(stack_start), end.min(STACK_LIMIT))
+    } else if block_heap {
+        (stack_end, end, stack_end)
+    } else {
+        (end, end, end.min(STACK_LIMIT))
+    }
+}
+
+/// Resizes the memory block of the program, returning the paragraphs available on failure.
+fn resize(paragraphs: u16) -> Result<(), u16> {
+    // ES still holds the segment of the PSP, which starts the block, and so does DS.
+    let mut regs = Registers { ax: 0x4A00, bx: paragraphs, ..Registers::new() };
+    unsafe { bios::int::<0x21>(&mut regs) };
+
+    if regs.carry() { Err(regs.bx) } else { Ok(()) }
+}
+
+/// Cleanup to run once `main` returns. The `dos` crate replaces this with its exit hooks.
+#[unsafe(no_mangle)]
+#[linkage = "weak"]
+extern "C" fn __dos_exit_hooks() {}
+
+#[cfg(not(test))]
 #[cold]
 #[panic_handler]
 #[linkage = "weak"]
-fn panic_handler(_info: &PanicInfo) -> ! {
+fn panic_handler(_info: &core::panic::PanicInfo) -> ! {
     loop {}
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn stack_stays_below_64_kib() {
+        assert_eq!(layout(0x10000, 0x3000, 0x5000, false, false), (0x10000, 0x10000, 0xFFFE));
+        assert_eq!(layout(0x10000, 0x3000, 0x5000, true, false), (0x3000, 0xE000, 0xFFFE));
+    }
+
+    #[test]
+    fn stack_follows_a_smaller_block() {
+        assert_eq!(layout(0x8000, 0x3000, 0x5000, false, false), (0x8000, 0x8000, 0x8000));
+        assert_eq!(layout(0x8000, 0x3000, 0x5000, true, false), (0x3000, 0x6000, 0x8000));
+    }
+
+    #[test]
+    fn near_heap_is_empty_without_room() {
+        assert_eq!(layout(0x4000, 0x3000, 0x5000, true, false), (0x3000, 0x3000, 0x4000));
+    }
+
+    #[test]
+    fn block_heap_moves_the_stack_after_bss() {
+        assert_eq!(layout(0x10000, 0x3000, 0x5000, false, true), (0x5000, 0x10000, 0x5000));
+    }
+}
@@ -7,5 +7,24 @@
 pub use alloc_crate::alloc::*;
 
 /// The default memory allocator provided by the operating system.
+///
+/// Small allocations are carved from 4 KiB arenas, larger ones get a DOS memory block of
+/// their own (int 21h, AH=48h). All of it has to come from the 64 KiB segment of the program,
+/// the memory that pointers can reach.
+///
+/// For DOS to put those blocks in the segment, the stack cannot stay at its top. It moves to
+/// right after the program instead, and is 8 KiB unless linked with a different size, as in
+/// `-C link-arg=--defsym=__stack_size=16K`.
+///
+/// With the `near_heap` feature, the heap instead lives between the end of the program and the
+/// stack, without DOS being involved. See [`heap_size`]. The stack keeps the top of the segment
+/// and the heap stops short of the stack size below it, but nothing stops a deeper stack from
+/// running into the heap.
+///
+/// [`heap_size`]: crate::os::dos::alloc::heap_size
 #[derive(Debug, Default, Clone, Copy)]
 pub struct System;
+
+#[cfg(not(test))]
+#[global_allocator]
+static GLOBAL: System = System;
@@ -0,0 +1,39 @@
+//! Inspection and manipulation of the process's environment.
+
+use crate::io;
+use crate::os::dos::fs::drive_of;
+use crate::path::PathBuf;
+use core::ffi::CStr;
+use dos::drive;
+
+/// Returns the current working directory, made of the current drive and that drive's current
+/// directory, as in `C:\DOS`.
+pub fn current_dir() -> io::Result<PathBuf> {
+    let drive = drive::current();
+    let mut buf = [0; 64];
+    let dir = dos::fs::current_dir(Some(drive), &mut buf)?;
+
+    let mut path = PathBuf::new();
+    path.push(&[drive.letter(), b':', b'\\'])?;
+    path.push(dir)?;
+    Ok(path)
+}
+
+/// Changes the current working directory to the specified path.
+///
+/// DOS keeps a current directory per drive. If `path` starts with a drive letter, that
+/// drive's current directory is changed and the drive becomes the current drive, so `D:`
+/// alone only switches drives.
+pub fn set_current_dir(path: *const u8) -> io::Result<()> {
+    let bytes = unsafe { CStr::from_ptr(path.cast()) }.to_bytes();
+
+    if !matches!(bytes, [_, b':']) {
+        dos::fs::set_current_dir(path)?;
+    }
+
+    if let [_, b':', ..] = bytes {
+        drive::set_current(drive_of(path)?)?;
+    }
+
+    Ok(())
+}
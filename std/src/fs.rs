@@ -1,107 +1,409 @@
+use crate::bstr::ByteStr;
+use crate::io::{self, Read, Seek, SeekFrom, Write};
+use crate::sys::fs as fs_imp;
 use crate::time::SystemTime;
-use core::arch::asm;
 
+/// An object providing access to an open file on the filesystem.
+///
+/// The file is closed when the value is dropped.
+#[derive(Debug)]
 pub struct File {
-    handle: u16,
+    inner: fs_imp::File,
 }
 
 impl File {
     /// Opens a file in write-only mode.
     #[inline]
-    pub fn create(path: *const u8) -> Result<Self, ()> {
+    pub fn create(path: *const u8) -> io::Result<Self> {
         OpenOptions::new().write(true).create(true).truncate(true).open(path)
     }
 
     /// Creates a new file in read-write mode; error if the file exists.
     #[inline]
-    pub fn create_new(path: *const u8) -> Result<Self, ()> {
+    pub fn create_new(path: *const u8) -> io::Result<Self> {
         OpenOptions::new().read(true).write(true).create_new(true).open(path)
     }
 
     /// Attempts to open a file in read-only mode.
     #[inline]
-    pub fn open(path: *const u8) -> Result<Self, ()> {
+    pub fn open(path: *const u8) -> io::Result<Self> {
         OpenOptions::new().read(true).open(path)
     }
 
-    pub fn metadata(&self) -> Metadata {
-        todo!()
+    /// Queries metadata about the underlying file.
+    ///
+    /// DOS cannot return the attributes of an open file, so [`Metadata::permissions`] is never
+    /// read-only here. Use [`metadata`] with the path to get them.
+    #[inline]
+    pub fn metadata(&self) -> io::Result<Metadata> {
+        self.inner.file_attr().map(Metadata)
+    }
+
+    /// Changes the timestamps of the underlying file.
+    ///
+    /// DOS stores them with two second granularity, and only DOS 7 and later have the access
+    /// and creation times. Times before 1980 or after 2107 are rejected.
+    #[inline]
+    pub fn set_times(&self, times: FileTimes) -> io::Result<()> {
+        self.inner.set_times(times.0)
+    }
+
+    /// Changes the modification time of the underlying file.
+    ///
+    /// This is an alias for `set_times(FileTimes::new().set_modified(time))`.
+    #[inline]
+    pub fn set_modified(&self, time: SystemTime) -> io::Result<()> {
+        self.set_times(FileTimes::new().set_modified(time))
     }
 }
 
-impl Drop for File {
+impl Read for File {
     #[inline]
-    fn drop(&mut self) {
-        unsafe { asm!("int 0x21", in("ah") 0x3Eu8, in("bx") self.handle, options(nostack)) }
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        self.inner.read(buf)
     }
 }
 
-#[derive(Debug, Default, Clone)]
-pub struct OpenOptions {
-    append: bool,
-    create: bool,
-    create_new: bool,
-    read: bool,
-    truncate: bool,
-    write: bool,
+impl Read for &File {
+    #[inline]
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        self.inner.read(buf)
+    }
+}
+
+impl Write for File {
+    #[inline]
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        self.inner.write(buf)
+    }
+
+    #[inline]
+    fn flush(&mut self) -> io::Result<()> {
+        self.inner.flush()
+    }
+}
+
+impl Write for &File {
+    #[inline]
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        self.inner.write(buf)
+    }
+
+    #[inline]
+    fn flush(&mut self) -> io::Result<()> {
+        self.inner.flush()
+    }
+}
+
+impl Seek for File {
+    #[inline]
+    fn seek(&mut self, pos: SeekFrom) -> io::Result<u64> {
+        self.inner.seek(pos)
+    }
 }
 
+impl Seek for &File {
+    #[inline]
+    fn seek(&mut self, pos: SeekFrom) -> io::Result<u64> {
+        self.inner.seek(pos)
+    }
+}
+
+/// Options and flags which can be used to configure how a file is opened.
+///
+/// The semantics follow the portable standard library: `truncate` requires `write`,
+/// `create` and `create_new` require `write` or `append`, and `append` cannot be
+/// combined with `truncate`. Invalid combinations fail with [`io::ErrorKind::InvalidInput`].
+#[derive(Debug, Default, Clone)]
+pub struct OpenOptions(fs_imp::OpenOptions);
+
 impl OpenOptions {
     #[inline]
     pub const fn new() -> Self {
-        Self { append: false, create: false, create_new: false, read: false, truncate: false, write: false }
+        Self(fs_imp::OpenOptions::new())
     }
 
     #[inline]
     pub const fn append(&mut self, append: bool) -> &mut Self {
-        self.append = append;
+        self.0.append(append);
         self
     }
 
     #[inline]
     pub const fn create(&mut self, create: bool) -> &mut Self {
-        self.create = create;
+        self.0.create(create);
         self
     }
 
     #[inline]
     pub const fn create_new(&mut self, create_new: bool) -> &mut Self {
-        self.create_new = create_new;
+        self.0.create_new(create_new);
         self
     }
 
     #[inline]
     pub const fn read(&mut self, read: bool) -> &mut Self {
-        self.read = read;
+        self.0.read(read);
         self
     }
 
     #[inline]
     pub const fn truncate(&mut self, truncate: bool) -> &mut Self {
-        self.truncate = truncate;
+        self.0.truncate(truncate);
         self
     }
 
     #[inline]
     pub const fn write(&mut self, write: bool) -> &mut Self {
-        self.write = write;
+        self.0.write(write);
         self
     }
 
     #[inline]
-    pub fn open(&self, path: *const u8) -> Result<File, ()> {
-        let handle: u16;
-        let flags: u16;
-        unsafe { asm!("int 0x21", in("ah") 0x3Du8, in("al") 0u8, in("dx") path as u16, lateout("ax") handle, options(nostack)) }
-        unsafe { asm!("pushf", "pop {0:x}", out(reg) flags) }
-        if flags & 1 != 0 { Ok(File { handle }) } else { Err(()) }
+    pub fn open(&self, path: *const u8) -> io::Result<File> {
+        fs_imp::File::open(path, &self.0).map(|inner| File { inner })
     }
 }
 
-pub struct Metadata {}
+/// Metadata information about a file.
+#[derive(Debug, Clone)]
+pub struct Metadata(pub(crate) fs_imp::FileAttr);
 
+#[allow(clippy::len_without_is_empty)]
 impl Metadata {
-    pub fn modified(&self) -> SystemTime {
-        todo!()
+    /// Returns the file type for this metadata.
+    #[must_use]
+    pub fn file_type(&self) -> FileType {
+        FileType(self.0.file_type())
     }
+
+    /// Returns `true` if this metadata is for a directory.
+    #[must_use]
+    pub fn is_dir(&self) -> bool {
+        self.file_type().is_dir()
+    }
+
+    /// Returns `true` if this metadata is for a regular file.
+    #[must_use]
+    pub fn is_file(&self) -> bool {
+        self.file_type().is_file()
+    }
+
+    /// Returns the size of the file, in bytes, this metadata is for.
+    #[must_use]
+    pub fn len(&self) -> u64 {
+        self.0.size()
+    }
+
+    /// Returns the permissions of the file this metadata is for.
+    #[must_use]
+    pub fn permissions(&self) -> Permissions {
+        Permissions(self.0.perm())
+    }
+
+    /// Returns the last modification time listed in this metadata.
+    ///
+    /// DOS records it in local time with two second granularity.
+    #[inline]
+    pub fn modified(&self) -> io::Result<SystemTime> {
+        self.0.modified()
+    }
+
+    /// Returns the last access time of this metadata.
+    ///
+    /// Only DOS 7 and later record it, and only to the day.
+    #[inline]
+    pub fn accessed(&self) -> io::Result<SystemTime> {
+        self.0.accessed()
+    }
+
+    /// Returns the creation time listed in this metadata.
+    ///
+    /// Only DOS 7 and later record it.
+    #[inline]
+    pub fn created(&self) -> io::Result<SystemTime> {
+        self.0.created()
+    }
+}
+
+/// Representation of the various timestamps on a file.
+#[derive(Debug, Default, Clone, Copy)]
+pub struct FileTimes(pub(crate) fs_imp::FileTimes);
+
+impl FileTimes {
+    /// Creates a new `FileTimes` with no times set.
+    ///
+    /// Using the resulting `FileTimes` in [`File::set_times`] will not modify any timestamps.
+    #[must_use]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Set the last access time of a file.
+    #[must_use]
+    pub fn set_accessed(mut self, t: SystemTime) -> Self {
+        self.0.set_accessed(t);
+        self
+    }
+
+    /// Set the last modified time of a file.
+    #[must_use]
+    pub fn set_modified(mut self, t: SystemTime) -> Self {
+        self.0.set_modified(t);
+        self
+    }
+}
+
+/// Representation of the various permissions on a file.
+///
+/// DOS has no owners, so this only holds the attribute byte. Besides read-only, the hidden,
+/// system and archive attributes are reachable through [`PermissionsExt`].
+///
+/// [`PermissionsExt`]: crate::os::dos::fs::PermissionsExt
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct Permissions(pub(crate) fs_imp::FilePermissions);
+
+impl Permissions {
+    /// Returns `true` if these permissions describe a read-only file.
+    #[must_use]
+    pub fn readonly(&self) -> bool {
+        self.0.readonly()
+    }
+
+    /// Modifies the read-only flag for this set of permissions.
+    ///
+    /// This does not modify the file itself, use [`set_permissions`] for that.
+    pub fn set_readonly(&mut self, readonly: bool) {
+        self.0.set_readonly(readonly);
+    }
+}
+
+/// A structure representing a type of file with accessors for each file type.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct FileType(pub(crate) fs_imp::FileType);
+
+impl FileType {
+    /// Tests whether this file type represents a directory.
+    #[must_use]
+    pub fn is_dir(&self) -> bool {
+        self.0.is_dir()
+    }
+
+    /// Tests whether this file type represents a regular file, which neither a directory nor
+    /// a volume label is.
+    #[must_use]
+    pub fn is_file(&self) -> bool {
+        self.0.is_file()
+    }
+
+    /// Tests whether this file type represents a symbolic link, which DOS does not have.
+    #[must_use]
+    pub fn is_symlink(&self) -> bool {
+        self.0.is_symlink()
+    }
+}
+
+/// Iterator over the entries in a directory.
+///
+/// This iterator is returned from the [`read_dir`] function and will yield instances of
+/// <code>[io::Result]<[DirEntry]></code>. The `.` and `..` entries are skipped.
+pub struct ReadDir(fs_imp::ReadDir);
+
+impl Iterator for ReadDir {
+    type Item = io::Result<DirEntry>;
+
+    #[inline]
+    fn next(&mut self) -> Option<Self::Item> {
+        self.0.next().map(|entry| entry.map(DirEntry))
+    }
+}
+
+/// Entries returned by the [`ReadDir`] iterator.
+#[derive(Debug, Clone)]
+pub struct DirEntry(fs_imp::DirEntry);
+
+impl DirEntry {
+    /// Returns the file name of this directory entry without any leading path component.
+    #[must_use]
+    pub fn file_name(&self) -> &ByteStr {
+        ByteStr::new(self.0.file_name())
+    }
+
+    /// Returns the file type for the file that this entry points at.
+    pub fn file_type(&self) -> io::Result<FileType> {
+        self.0.file_type().map(FileType)
+    }
+
+    /// Returns the metadata for the file that this entry points at.
+    ///
+    /// The metadata is captured while enumerating the directory, so no further call to DOS is made.
+    pub fn metadata(&self) -> io::Result<Metadata> {
+        self.0.metadata().map(Metadata)
+    }
+}
+
+/// Returns an iterator over the entries within a directory.
+///
+/// Hidden and system files as well as subdirectories are included.
+pub fn read_dir(path: *const u8) -> io::Result<ReadDir> {
+    fs_imp::readdir(path).map(ReadDir)
+}
+
+/// Creates a new, empty directory at the provided path.
+///
+/// Fails if `path` already exists or its parent does not.
+#[inline]
+pub fn create_dir(path: *const u8) -> io::Result<()> {
+    fs_imp::mkdir(path)
+}
+
+/// Recursively creates a directory and all of its parent components if they are missing.
+///
+/// Succeeds if `path` already is a directory.
+#[inline]
+pub fn create_dir_all(path: *const u8) -> io::Result<()> {
+    fs_imp::mkdir_all(path)
+}
+
+/// Removes an empty directory.
+#[inline]
+pub fn remove_dir(path: *const u8) -> io::Result<()> {
+    fs_imp::rmdir(path)
+}
+
+/// Removes a directory at this path, after removing all its contents.
+///
+/// Read-only files are not removed, and cause this function to fail.
+#[inline]
+pub fn remove_dir_all(path: *const u8) -> io::Result<()> {
+    fs_imp::remove_dir_all(path)
+}
+
+/// Removes a file from the filesystem.
+#[inline]
+pub fn remove_file(path: *const u8) -> io::Result<()> {
+    fs_imp::unlink(path)
+}
+
+/// Changes the permissions found on a file or a directory.
+#[inline]
+pub fn set_permissions(path: *const u8, perm: Permissions) -> io::Result<()> {
+    fs_imp::set_perm(path, perm.0)
+}
+
+/// Given a path, queries the file system to get information about a file or directory.
+///
+/// The file does not need to be opened, so this also works on directories and files that
+/// cannot be opened, such as read-only files on a read-only share.
+#[inline]
+pub fn metadata(path: *const u8) -> io::Result<Metadata> {
+    fs_imp::stat(path).map(Metadata)
+}
+
+/// Queries the metadata about a file without following symlinks.
+///
+/// DOS has no symbolic links, so this is the same as [`metadata`].
+#[inline]
+pub fn symlink_metadata(path: *const u8) -> io::Result<Metadata> {
+    metadata(path)
 }
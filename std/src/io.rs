@@ -1,6 +1,8 @@
 use crate::cp437::CP437Char;
 use crate::sys::Service;
-use core::arch::asm;
+use bios::Registers;
+use core::fmt::Display;
+use core::mem::ManuallyDrop;
 use thiserror::Error;
 
 pub fn print(str: &str) {
@@ -24,7 +26,8 @@ pub fn print(str: &str) {
 
 pub fn print_char(ch: char) {
     let ch = if let Some(c) = CP437Char::new(ch) { c.as_byte() } else { b'?' };
-    unsafe { asm!("int 0x21", in("ah") Service::PrintChar as u8, in("dl") ch, options(nostack, preserves_flags)) };
+    let mut regs = Registers { ax: (Service::PrintChar as u16) << 8, dx: ch as u16, ..Registers::new() };
+    unsafe { bios::int::<0x21>(&mut regs) };
 }
 
 /// A specialized [`Result`] type for I/O operations.
@@ -35,15 +38,167 @@ pub fn print_char(ch: char) {
 /// [`dos::io`]: crate::io
 pub type Result<T> = core::result::Result<T, Error>;
 
+/// The error type for I/O operations of the [`Read`], [`Write`], [`Seek`], and
+/// associated traits.
+///
+/// Errors mostly originate from DOS itself, but may also be constructed from an
+/// [`ErrorKind`] when the failure is detected before DOS is called.
+#[derive(Error, Debug)]
+#[error("{repr}")]
+pub struct Error {
+    repr: Repr,
+}
+
 #[derive(Error, Debug)]
-#[error("")]
-pub struct Error {}
+enum Repr {
+    #[error(transparent)]
+    Os(dos::Error),
+    #[error("{}", .0.as_str())]
+    Simple(ErrorKind),
+    #[error("{1}")]
+    SimpleMessage(ErrorKind, &'static str),
+}
+
+impl Error {
+    /// Creates a new I/O error from a known kind of error and a static message.
+    #[inline]
+    pub(crate) const fn new_const(kind: ErrorKind, message: &'static str) -> Self {
+        Self { repr: Repr::SimpleMessage(kind, message) }
+    }
+
+    /// Creates a new instance of an [`Error`] from a particular DOS error code.
+    #[inline]
+    #[must_use]
+    pub fn from_raw_os_error(code: i32) -> Self {
+        Self { repr: Repr::Os(dos::Error::from(code as u8)) }
+    }
+
+    /// Returns the DOS error code that this error represents, if any.
+    #[inline]
+    #[must_use]
+    pub fn raw_os_error(&self) -> Option<i32> {
+        match self.repr {
+            Repr::Os(error) => Some(error.code().into()),
+            Repr::Simple(_) | Repr::SimpleMessage(..) => None,
+        }
+    }
+
+    /// Returns the corresponding [`ErrorKind`] for this error.
+    #[must_use]
+    pub fn kind(&self) -> ErrorKind {
+        match self.repr {
+            Repr::Os(ref error) => crate::sys::decode_error_kind(error),
+            Repr::Simple(kind) | Repr::SimpleMessage(kind, _) => kind,
+        }
+    }
+}
+
+impl From<ErrorKind> for Error {
+    #[inline]
+    fn from(kind: ErrorKind) -> Self {
+        Self { repr: Repr::Simple(kind) }
+    }
+}
+
+impl From<dos::Error> for Error {
+    #[inline]
+    fn from(error: dos::Error) -> Self {
+        Self { repr: Repr::Os(error) }
+    }
+}
+
+/// A list specifying general categories of I/O error.
+///
+/// This list is intended to grow over time and it is not recommended to
+/// exhaustively match against it.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[non_exhaustive]
+pub enum ErrorKind {
+    /// An entity was not found, often a file.
+    NotFound,
+    /// The operation lacked the necessary privileges to complete.
+    PermissionDenied,
+    /// An entity already exists, often a file.
+    AlreadyExists,
+    /// A filesystem object is, unexpectedly, not a directory.
+    NotADirectory,
+    /// The filesystem object is, unexpectedly, a directory.
+    IsADirectory,
+    /// A non-empty directory was specified where an empty directory was expected.
+    DirectoryNotEmpty,
+    /// The underlying storage is full.
+    StorageFull,
+    /// Resource is busy.
+    ResourceBusy,
+    /// A parameter was incorrect.
+    InvalidInput,
+    /// Data not valid for the operation were encountered.
+    InvalidData,
+    /// An error returned when an operation could not be completed because a
+    /// call to [`write`] returned [`Ok(0)`].
+    ///
+    /// [`write`]: Write::write
+    /// [`Ok(0)`]: Ok
+    WriteZero,
+    /// This operation is unsupported on this platform.
+    Unsupported,
+    /// An error returned when an operation could not be completed because an
+    /// "end of file" was reached prematurely.
+    UnexpectedEof,
+    /// An operation could not be completed, because it failed
+    /// to allocate enough memory.
+    OutOfMemory,
+    /// A custom error that does not fall under any other I/O error kind.
+    Other,
+}
+
+impl ErrorKind {
+    pub(crate) const fn as_str(&self) -> &'static str {
+        match *self {
+            Self::NotFound => "entity not found",
+            Self::PermissionDenied => "permission denied",
+            Self::AlreadyExists => "entity already exists",
+            Self::NotADirectory => "not a directory",
+            Self::IsADirectory => "is a directory",
+            Self::DirectoryNotEmpty => "directory not empty",
+            Self::StorageFull => "no storage space",
+            Self::ResourceBusy => "resource busy",
+            Self::InvalidInput => "invalid input parameter",
+            Self::InvalidData => "invalid data",
+            Self::WriteZero => "write zero",
+            Self::Unsupported => "unsupported",
+            Self::UnexpectedEof => "unexpected end of file",
+            Self::OutOfMemory => "out of memory",
+            Self::Other => "other error",
+        }
+    }
+}
+
+impl Display for ErrorKind {
+    #[inline]
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
 
 pub trait Read {
     /// Pull some bytes from this source into the specified buffer, returning
     /// how many bytes were read.
     fn read(&mut self, buf: &mut [u8]) -> Result<usize>;
 
+    /// Reads the exact number of bytes required to fill `buf`.
+    ///
+    /// Fails with [`ErrorKind::UnexpectedEof`] if the source ends before `buf` is filled.
+    fn read_exact(&mut self, mut buf: &mut [u8]) -> Result<()> {
+        while !buf.is_empty() {
+            match self.read(buf)? {
+                0 => return Err(Error::new_const(ErrorKind::UnexpectedEof, "failed to fill whole buffer")),
+                n => buf = &mut buf[n..],
+            }
+        }
+        Ok(())
+    }
+
     /// Creates a "by reference" adaptor for this instance of `Read`.
     fn by_ref(&mut self) -> &mut Self
     where
@@ -56,6 +211,61 @@ pub trait Read {
 pub trait Write {
     /// Writes a buffer into this writer, returning how many bytes were written.
     fn write(&mut self, buf: &[u8]) -> Result<usize>;
+
+    /// Flushes this output stream, ensuring that all intermediately buffered
+    /// contents reach their destination.
+    fn flush(&mut self) -> Result<()>;
+
+    /// Attempts to write an entire buffer into this writer.
+    ///
+    /// Fails with [`ErrorKind::WriteZero`] if the writer stops accepting bytes, e.g. when the disk is full.
+    fn write_all(&mut self, mut buf: &[u8]) -> Result<()> {
+        while !buf.is_empty() {
+            match self.write(buf)? {
+                0 => return Err(Error::new_const(ErrorKind::WriteZero, "failed to write whole buffer")),
+                n => buf = &buf[n..],
+            }
+        }
+        Ok(())
+    }
+
+    /// Creates a "by reference" adapter for this instance of `Write`.
+    fn by_ref(&mut self) -> &mut Self
+    where
+        Self: Sized,
+    {
+        self
+    }
+}
+
+/// Enumeration of possible methods to seek within an I/O object.
+///
+/// It is used by the [`Seek`] trait.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SeekFrom {
+    /// Sets the offset to the provided number of bytes.
+    Start(u64),
+    /// Sets the offset to the size of this object plus the specified number of bytes.
+    End(i64),
+    /// Sets the offset to the current position plus the specified number of bytes.
+    Current(i64),
+}
+
+/// The `Seek` trait provides a cursor which can be moved within a stream of bytes.
+pub trait Seek {
+    /// Seek to an offset, in bytes, in a stream, returning the new position from the start of the stream.
+    fn seek(&mut self, pos: SeekFrom) -> Result<u64>;
+
+    /// Rewind to the beginning of a stream.
+    fn rewind(&mut self) -> Result<()> {
+        self.seek(SeekFrom::Start(0))?;
+        Ok(())
+    }
+
+    /// Returns the current seek position from the start of the stream.
+    fn stream_position(&mut self) -> Result<u64> {
+        self.seek(SeekFrom::Current(0))
+    }
 }
 
 /// A handle to the global standard output stream of the current process.
@@ -63,10 +273,13 @@ pub struct Stdout {}
 
 impl Write for Stdout {
     fn write(&mut self, buf: &[u8]) -> Result<usize> {
-        let count;
-        unsafe {
-            asm!("int 0x21", in("ah") 0x40u8, in("bx") 1u16, in("cx") buf.len() as u16, in("dx") buf.as_ptr(), lateout("ax") count, options(nostack))
-        }
-        Ok(count)
+        // Standard output is owned by the process, so it must never be closed.
+        let stdout = ManuallyDrop::new(unsafe { dos::fs::File::from_handle(1) });
+        Ok(stdout.write(buf)?)
+    }
+
+    #[inline]
+    fn flush(&mut self) -> Result<()> {
+        Ok(())
     }
 }
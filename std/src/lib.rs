@@ -37,11 +37,14 @@ pub mod alloc;
 pub mod ascii;
 pub mod bstr;
 pub mod cp437;
+pub mod env;
 pub mod fs;
 pub mod io;
+pub mod os;
 pub mod path;
 pub mod process;
 pub mod random;
+pub mod thread;
 pub mod time;
 
 pub use core::{
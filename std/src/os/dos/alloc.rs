@@ -0,0 +1,14 @@
+//! DOS-specific extensions to the [`std::alloc`] module.
+//!
+//! [`std::alloc`]: crate::alloc
+
+/// Returns the size of the heap in bytes, as set up at startup.
+///
+/// With the `near_heap` feature, this is the room between the end of the program and the
+/// stack at the top of the segment. Otherwise it is the rest of the segment past the stack,
+/// which DOS hands out in blocks, each taking a paragraph of its own for bookkeeping.
+#[inline]
+#[must_use]
+pub fn heap_size() -> usize {
+    crate::sys::alloc::heap_size()
+}
@@ -0,0 +1,112 @@
+//! DOS-specific extensions to primitives in the [`std::fs`] module.
+//!
+//! [`std::fs`]: crate::fs
+
+use crate::fs::{FileTimes, FileType, Metadata, Permissions};
+use crate::io::{self, ErrorKind};
+use crate::sys::fs::FilePermissions;
+use crate::time::SystemTime;
+use core::ffi::CStr;
+use dos::drive::{self, Drive};
+
+pub use dos::drive::{DriveType, Space, drives};
+pub use dos::fs::Flags;
+
+/// DOS-specific extensions to [`Permissions`], giving access to the whole attribute byte.
+pub trait PermissionsExt {
+    /// Returns the attributes, such as [`Flags::H`] for hidden files.
+    fn flags(&self) -> Flags;
+
+    /// Sets the attributes. The directory and volume attributes are ignored when applied with
+    /// [`set_permissions`].
+    ///
+    /// [`set_permissions`]: crate::fs::set_permissions
+    fn set_flags(&mut self, flags: Flags);
+
+    /// Creates permissions from the attributes.
+    fn from_flags(flags: Flags) -> Self;
+}
+
+impl PermissionsExt for Permissions {
+    #[inline]
+    fn flags(&self) -> Flags {
+        self.0.flags()
+    }
+
+    #[inline]
+    fn set_flags(&mut self, flags: Flags) {
+        self.0.set_flags(flags);
+    }
+
+    #[inline]
+    fn from_flags(flags: Flags) -> Self {
+        Self(FilePermissions::from_flags(flags))
+    }
+}
+
+/// Returns the drive `path` is on: the drive letter it starts with, or else the current drive.
+pub fn drive_of(path: *const u8) -> io::Result<Drive> {
+    match unsafe { CStr::from_ptr(path.cast()) }.to_bytes() {
+        [letter, b':', ..] => Drive::from_letter(*letter).ok_or(io::Error::new_const(ErrorKind::InvalidInput, "invalid drive letter")),
+        _ => Ok(drive::current()),
+    }
+}
+
+/// Returns the number of bytes free on the volume holding `path`.
+#[inline]
+pub fn available_space(path: *const u8) -> io::Result<u64> {
+    Ok(drive_of(path)?.space()?.free())
+}
+
+/// Returns the size in bytes of the volume holding `path`.
+#[inline]
+pub fn total_space(path: *const u8) -> io::Result<u64> {
+    Ok(drive_of(path)?.space()?.total())
+}
+
+/// Returns the kind of drive holding `path`.
+#[inline]
+pub fn drive_type(path: *const u8) -> io::Result<DriveType> {
+    Ok(drive_of(path)?.drive_type()?)
+}
+
+/// DOS-specific extensions to [`FileTimes`].
+pub trait FileTimesExt {
+    /// Set the creation time of a file. Only DOS 7 and later support this.
+    #[must_use]
+    fn set_created(self, t: SystemTime) -> Self;
+}
+
+impl FileTimesExt for FileTimes {
+    #[inline]
+    fn set_created(mut self, t: SystemTime) -> Self {
+        self.0.set_created(t);
+        self
+    }
+}
+
+/// DOS-specific extensions to [`Metadata`].
+pub trait MetadataExt {
+    /// Returns the attributes of the directory entry.
+    fn flags(&self) -> Flags;
+}
+
+impl MetadataExt for Metadata {
+    #[inline]
+    fn flags(&self) -> Flags {
+        self.0.attributes()
+    }
+}
+
+/// DOS-specific extensions to [`FileType`].
+pub trait FileTypeExt {
+    /// Returns `true` if this file type is a volume label.
+    fn is_volume_label(&self) -> bool;
+}
+
+impl FileTypeExt for FileType {
+    #[inline]
+    fn is_volume_label(&self) -> bool {
+        self.0.is_volume_label()
+    }
+}
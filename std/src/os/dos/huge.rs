@@ -0,0 +1,636 @@
+//! Collections larger than conventional memory, kept in extended or expanded memory.
+//!
+//! The contents live in an [`XmsBlock`], or an [`EmsHandle`] where there is no XMS driver or
+//! it is out of memory. Neither can be addressed directly in real mode, so a window of 1 KiB
+//! in conventional memory holds the part last used, and is written back when another part is
+//! needed.
+//!
+//! [`XmsBlock`]: dos::xms::XmsBlock
+//! [`EmsHandle`]: dos::ems::EmsHandle
+
+use crate::io::{self, ErrorKind, Read, Seek, SeekFrom, Write};
+use core::marker::PhantomData;
+use core::mem::MaybeUninit;
+use dos::ems::{self, EmsError, EmsHandle};
+#[cfg(not(test))]
+use dos::xms;
+use dos::xms::{XmsBlock, XmsError};
+
+/// The size of the window, which the capacity is always a multiple of.
+const WINDOW: usize = 1024;
+
+/// Where the contents of a [`HugeBuffer`] live.
+#[derive(Debug)]
+#[cfg_attr(test, allow(dead_code))]
+enum Storage {
+    Xms(XmsBlock),
+    Ems(EmsHandle),
+    /// Plain memory, so the window logic can be tested without a memory manager.
+    #[cfg(test)]
+    Memory(alloc_crate::vec::Vec<u8>),
+}
+
+impl Storage {
+    /// Allocates at least `capacity` bytes, from XMS if there is a driver, else or if that
+    /// fails from EMS. The error from XMS is returned if there is no EMS either.
+    #[cfg(not(test))]
+    fn new(capacity: u32) -> io::Result<Self> {
+        let mut error = io::Error::new_const(ErrorKind::Unsupported, "no extended or expanded memory");
+
+        if xms::is_installed() {
+            let kib = u16::try_from(capacity.div_ceil(1024)).map_err(|_| too_large())?;
+
+            match XmsBlock::new(kib) {
+                Ok(block) => return Ok(Self::Xms(block)),
+                Err(xms) => error = xms_error(xms),
+            }
+        }
+
+        if ems::is_installed() {
+            let pages = u16::try_from(capacity.div_ceil(ems::PAGE_SIZE as u32)).map_err(|_| too_large())?;
+            return EmsHandle::new(pages).map(Self::Ems).map_err(ems_error);
+        }
+
+        Err(error)
+    }
+
+    /// Allocates `capacity` bytes of plain memory, filled with garbage as fresh storage would be.
+    #[cfg(test)]
+    fn new(capacity: u32) -> io::Result<Self> {
+        Ok(Self::Memory(alloc_crate::vec![0xAA; capacity as usize]))
+    }
+
+    fn capacity(&self) -> u32 {
+        match self {
+            Self::Xms(block) => block.len(),
+            Self::Ems(handle) => handle.pages() as u32 * ems::PAGE_SIZE as u32,
+            #[cfg(test)]
+            Self::Memory(bytes) => bytes.len() as u32,
+        }
+    }
+
+    /// Grows to at least `capacity` bytes, keeping the contents.
+    fn grow(&mut self, capacity: u32) -> io::Result<()> {
+        match self {
+            Self::Xms(block) => {
+                let kib = u16::try_from(capacity.div_ceil(1024)).map_err(|_| too_large())?;
+                block.resize(kib).map_err(xms_error)
+            }
+            Self::Ems(handle) => {
+                let pages = u16::try_from(capacity.div_ceil(ems::PAGE_SIZE as u32)).map_err(|_| too_large())?;
+                handle.resize(pages).map_err(ems_error)
+            }
+            #[cfg(test)]
+            Self::Memory(bytes) => {
+                bytes.resize(capacity as usize, 0xAA);
+                Ok(())
+            }
+        }
+    }
+
+    fn read(&mut self, offset: u32, buf: &mut [u8]) -> io::Result<()> {
+        match self {
+            Self::Xms(block) => block.read(offset, buf).map_err(xms_error),
+            Self::Ems(handle) => handle.read(offset, buf).map_err(ems_error),
+            #[cfg(test)]
+            Self::Memory(bytes) => {
+                buf.copy_from_slice(&bytes[offset as usize..offset as usize + buf.len()]);
+                Ok(())
+            }
+        }
+    }
+
+    fn write(&mut self, offset: u32, buf: &[u8]) -> io::Result<()> {
+        match self {
+            Self::Xms(block) => block.write(offset, buf).map_err(xms_error),
+            Self::Ems(handle) => handle.write(offset, buf).map_err(ems_error),
+            #[cfg(test)]
+            Self::Memory(bytes) => {
+                bytes[offset as usize..offset as usize + buf.len()].copy_from_slice(buf);
+                Ok(())
+            }
+        }
+    }
+}
+
+fn too_large() -> io::Error {
+    io::Error::new_const(ErrorKind::OutOfMemory, "capacity too large")
+}
+
+fn xms_error(error: XmsError) -> io::Error {
+    match error {
+        XmsError::OutOfMemory | XmsError::OutOfHandles => io::Error::new_const(ErrorKind::OutOfMemory, "out of extended memory"),
+        XmsError::NotImplemented => io::Error::new_const(ErrorKind::Unsupported, "no XMS driver"),
+        _ => io::Error::new_const(ErrorKind::Other, "XMS driver error"),
+    }
+}
+
+fn ems_error(error: EmsError) -> io::Error {
+    match error {
+        EmsError::OutOfHandles | EmsError::NotEnoughPages | EmsError::NotEnoughFreePages => {
+            io::Error::new_const(ErrorKind::OutOfMemory, "out of expanded memory")
+        }
+        EmsError::UndefinedFunction => io::Error::new_const(ErrorKind::Unsupported, "no expanded memory manager"),
+        _ => io::Error::new_const(ErrorKind::Other, "expanded memory manager error"),
+    }
+}
+
+/// A growable byte buffer in extended or expanded memory.
+///
+/// Besides random access through [`read_at`] and [`write_at`], it is a cursor over its contents
+/// implementing [`Read`], [`Write`] and [`Seek`]. Writing past the end extends it, filling any
+/// gap with zeros.
+///
+/// [`read_at`]: HugeBuffer::read_at
+/// [`write_at`]: HugeBuffer::write_at
+#[derive(Debug)]
+pub struct HugeBuffer {
+    storage: Option<Storage>,
+    len: u32,
+    position: u64,
+    window: [u8; WINDOW],
+    /// The offset of the window in the storage, or [`None`] if it holds nothing.
+    window_start: Option<u32>,
+    /// Whether the window has changes not yet in the storage.
+    dirty: bool,
+}
+
+impl HugeBuffer {
+    /// Creates an empty buffer. Nothing is allocated until something is written.
+    #[inline]
+    #[must_use]
+    pub const fn new() -> Self {
+        Self { storage: None, len: 0, position: 0, window: [0; WINDOW], window_start: None, dirty: false }
+    }
+
+    /// Creates an empty buffer with room for at least `capacity` bytes.
+    pub fn with_capacity(capacity: u32) -> io::Result<Self> {
+        let mut buffer = Self::new();
+        buffer.reserve(capacity)?;
+        Ok(buffer)
+    }
+
+    /// Returns the length in bytes.
+    #[inline]
+    #[must_use]
+    pub const fn len(&self) -> u32 {
+        self.len
+    }
+
+    /// Returns `true` if the buffer holds no bytes.
+    #[inline]
+    #[must_use]
+    pub const fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// Returns the bytes the buffer can hold without growing.
+    #[inline]
+    #[must_use]
+    pub fn capacity(&self) -> u32 {
+        self.storage.as_ref().map_or(0, Storage::capacity)
+    }
+
+    /// Makes room for at least `additional` more bytes.
+    pub fn reserve(&mut self, additional: u32) -> io::Result<()> {
+        let needed = self.len.checked_add(additional).ok_or_else(too_large)?;
+
+        if needed <= self.capacity() {
+            return Ok(());
+        }
+
+        // Double to keep growing by single writes cheap, in whole windows.
+        let capacity = needed.max(self.capacity().saturating_mul(2)).next_multiple_of(WINDOW as u32);
+
+        match &mut self.storage {
+            Some(storage) => storage.grow(capacity),
+            None => Storage::new(capacity).map(|storage| self.storage = Some(storage)),
+        }
+    }
+
+    /// Shortens the buffer to `len` bytes. Does nothing if it is not longer than that.
+    #[inline]
+    pub fn truncate(&mut self, len: u32) {
+        self.len = self.len.min(len);
+    }
+
+    /// Empties the buffer, keeping its storage.
+    #[inline]
+    pub fn clear(&mut self) {
+        self.len = 0;
+    }
+
+    /// Copies the bytes at `offset` into `buf`. Fails with [`ErrorKind::UnexpectedEof`] if
+    /// they run past the end.
+    pub fn read_at(&mut self, mut offset: u32, mut buf: &mut [u8]) -> io::Result<()> {
+        if offset as u64 + buf.len() as u64 > self.len as u64 {
+            return Err(io::Error::new_const(ErrorKind::UnexpectedEof, "read past the end"));
+        }
+
+        while !buf.is_empty() {
+            let start = self.load(offset, false)?;
+            let chunk = buf.len().min(WINDOW - start);
+            buf[..chunk].copy_from_slice(&self.window[start..start + chunk]);
+            buf = &mut buf[chunk..];
+            offset += chunk as u32;
+        }
+
+        Ok(())
+    }
+
+    /// Copies `buf` to the bytes at `offset`, extending the buffer if they run past the end.
+    pub fn write_at(&mut self, mut offset: u32, mut buf: &[u8]) -> io::Result<()> {
+        let end = offset.checked_add(buf.len() as u32).ok_or_else(too_large)?;
+
+        if end > self.len {
+            self.reserve(end - self.len)?;
+        }
+
+        // Fill a gap between the end and `offset`, whose storage holds anything.
+        while self.len < offset {
+            let start = self.load(self.len, true)?;
+            let chunk = (WINDOW - start).min((offset - self.len) as usize);
+            self.window[start..start + chunk].fill(0);
+            self.len += chunk as u32;
+        }
+
+        while !buf.is_empty() {
+            let start = self.load(offset, true)?;
+            let chunk = buf.len().min(WINDOW - start);
+            self.window[start..start + chunk].copy_from_slice(&buf[..chunk]);
+            buf = &buf[chunk..];
+            offset += chunk as u32;
+        }
+
+        self.len = self.len.max(end);
+        Ok(())
+    }
+
+    /// Moves the window to the one holding `offset` and returns where in the window that is.
+    fn load(&mut self, offset: u32, write: bool) -> io::Result<usize> {
+        let start = offset - offset % WINDOW as u32;
+
+        if self.window_start != Some(start) {
+            self.write_back()?;
+
+            // Past the end there is nothing worth reading.
+            if start < self.len {
+                self.storage.as_mut().ok_or(ErrorKind::UnexpectedEof)?.read(start, &mut self.window)?;
+            }
+
+            self.window_start = Some(start);
+        }
+
+        self.dirty |= write;
+        Ok((offset - start) as usize)
+    }
+
+    /// Writes the window back to the storage if it has changed.
+    fn write_back(&mut self) -> io::Result<()> {
+        if let (Some(start), true, Some(storage)) = (self.window_start, self.dirty, &mut self.storage) {
+            storage.write(start, &self.window)?;
+            self.dirty = false;
+        }
+
+        Ok(())
+    }
+}
+
+impl Default for HugeBuffer {
+    #[inline]
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Read for HugeBuffer {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        let available = (self.len as u64).saturating_sub(self.position);
+        let len = buf.len().min(available as usize);
+
+        // At or past the end, where the position may not even fit the offsets.
+        if len == 0 {
+            return Ok(0);
+        }
+
+        let position = u32::try_from(self.position).map_err(|_| too_large())?;
+        self.read_at(position, &mut buf[..len])?;
+        self.position += len as u64;
+        Ok(len)
+    }
+}
+
+impl Write for HugeBuffer {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        let position = u32::try_from(self.position).map_err(|_| too_large())?;
+
+        self.write_at(position, buf)?;
+        self.position += buf.len() as u64;
+        Ok(buf.len())
+    }
+
+    /// Writes the window back, so the storage holds everything.
+    #[inline]
+    fn flush(&mut self) -> io::Result<()> {
+        self.write_back()
+    }
+}
+
+impl Seek for HugeBuffer {
+    fn seek(&mut self, pos: SeekFrom) -> io::Result<u64> {
+        let position = match pos {
+            SeekFrom::Start(offset) => Some(offset),
+            SeekFrom::End(offset) => (self.len as u64).checked_add_signed(offset),
+            SeekFrom::Current(offset) => self.position.checked_add_signed(offset),
+        };
+
+        self.position = position.ok_or(io::Error::new_const(ErrorKind::InvalidInput, "invalid seek to a negative or overflowing position"))?;
+        Ok(self.position)
+    }
+}
+
+/// A growable array of `T` in extended or expanded memory, see the [module docs](self).
+///
+/// Elements are copied in and out, so they must be [`Copy`], and every access can fail if the
+/// memory manager does.
+#[derive(Debug)]
+pub struct HugeVec<T: Copy> {
+    buffer: HugeBuffer,
+    len: u32,
+    phantom: PhantomData<T>,
+}
+
+impl<T: Copy> HugeVec<T> {
+    const SIZE: u32 = {
+        assert!(size_of::<T>() > 0, "zero-sized types are not supported");
+        size_of::<T>() as u32
+    };
+
+    /// Creates an empty vector. Nothing is allocated until something is pushed.
+    #[inline]
+    #[must_use]
+    pub const fn new() -> Self {
+        Self { buffer: HugeBuffer::new(), len: 0, phantom: PhantomData }
+    }
+
+    /// Creates an empty vector with room for at least `capacity` elements.
+    pub fn with_capacity(capacity: u32) -> io::Result<Self> {
+        let bytes = capacity.checked_mul(Self::SIZE).ok_or_else(too_large)?;
+        Ok(Self { buffer: HugeBuffer::with_capacity(bytes)?, len: 0, phantom: PhantomData })
+    }
+
+    /// Returns the number of elements.
+    #[inline]
+    #[must_use]
+    pub const fn len(&self) -> u32 {
+        self.len
+    }
+
+    /// Returns `true` if the vector holds no elements.
+    #[inline]
+    #[must_use]
+    pub const fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// Returns the element at `index`, or [`None`] if it is out of bounds.
+    pub fn get(&mut self, index: u32) -> io::Result<Option<T>> {
+        if index >= self.len {
+            return Ok(None);
+        }
+
+        let mut value = MaybeUninit::<T>::uninit();
+        let bytes = unsafe { core::slice::from_raw_parts_mut(value.as_mut_ptr().cast::<u8>(), size_of::<T>()) };
+        self.buffer.read_at(index * Self::SIZE, bytes)?;
+        Ok(Some(unsafe { value.assume_init() }))
+    }
+
+    /// Replaces the element at `index`. Fails with [`ErrorKind::InvalidInput`] if it is out of
+    /// bounds.
+    pub fn set(&mut self, index: u32, value: T) -> io::Result<()> {
+        if index >= self.len {
+            return Err(io::Error::new_const(ErrorKind::InvalidInput, "index out of bounds"));
+        }
+
+        self.buffer.write_at(index * Self::SIZE, bytes_of(&value))
+    }
+
+    /// Appends `value` to the end.
+    pub fn push(&mut self, value: T) -> io::Result<()> {
+        let offset = self.len.checked_mul(Self::SIZE).ok_or_else(too_large)?;
+
+        self.buffer.write_at(offset, bytes_of(&value))?;
+        self.len += 1;
+        Ok(())
+    }
+
+    /// Removes the last element and returns it, or [`None`] if the vector is empty.
+    pub fn pop(&mut self) -> io::Result<Option<T>> {
+        if self.len == 0 {
+            return Ok(None);
+        }
+
+        let value = self.get(self.len - 1)?;
+        self.truncate(self.len - 1);
+        Ok(value)
+    }
+
+    /// Shortens the vector to `len` elements. Does nothing if it is not longer than that.
+    #[inline]
+    pub fn truncate(&mut self, len: u32) {
+        self.len = self.len.min(len);
+        self.buffer.truncate(self.len * Self::SIZE);
+    }
+
+    /// Empties the vector, keeping its storage.
+    #[inline]
+    pub fn clear(&mut self) {
+        self.truncate(0);
+    }
+
+    /// Returns an iterator over copies of the elements.
+    #[inline]
+    pub fn iter(&mut self) -> Iter<'_, T> {
+        Iter { vec: self, index: 0 }
+    }
+
+    /// Returns the bytes of the elements, for reading and writing them in bulk.
+    #[inline]
+    #[must_use]
+    pub fn as_buffer(&mut self) -> &mut HugeBuffer {
+        &mut self.buffer
+    }
+}
+
+impl<T: Copy> Default for HugeVec<T> {
+    #[inline]
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<'a, T: Copy> IntoIterator for &'a mut HugeVec<T> {
+    type Item = io::Result<T>;
+    type IntoIter = Iter<'a, T>;
+
+    #[inline]
+    fn into_iter(self) -> Iter<'a, T> {
+        self.iter()
+    }
+}
+
+/// An iterator over the elements of a [`HugeVec`], see [`HugeVec::iter`].
+#[derive(Debug)]
+pub struct Iter<'a, T: Copy> {
+    vec: &'a mut HugeVec<T>,
+    index: u32,
+}
+
+impl<T: Copy> Iterator for Iter<'_, T> {
+    type Item = io::Result<T>;
+
+    fn next(&mut self) -> Option<io::Result<T>> {
+        let item = self.vec.get(self.index).transpose()?;
+        self.index += 1;
+        Some(item)
+    }
+
+    #[inline]
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let remaining = self.vec.len.saturating_sub(self.index) as usize;
+        (remaining, Some(remaining))
+    }
+}
+
+/// Returns the bytes of `value`.
+#[inline]
+fn bytes_of<T: Copy>(value: &T) -> &[u8] {
+    unsafe { core::slice::from_raw_parts((value as *const T).cast::<u8>(), size_of::<T>()) }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use alloc_crate::vec::Vec;
+
+    /// Returns the bytes of `buffer`, read back through [`HugeBuffer::read_at`].
+    fn contents(buffer: &mut HugeBuffer) -> Vec<u8> {
+        let mut bytes = alloc_crate::vec![0; buffer.len() as usize];
+        buffer.read_at(0, &mut bytes).unwrap();
+        bytes
+    }
+
+    #[test]
+    fn write_and_read_across_windows() {
+        let bytes: Vec<u8> = (0..3 * WINDOW + 100).map(|i| i as u8).collect();
+        let mut buffer = HugeBuffer::new();
+
+        buffer.write_at(0, &bytes).unwrap();
+        assert_eq!(buffer.len(), bytes.len() as u32);
+        assert_eq!(contents(&mut buffer), bytes);
+
+        // A read straddling a window boundary, starting away from the cached window.
+        let mut straddling = [0; 8];
+        buffer.read_at(WINDOW as u32 - 4, &mut straddling).unwrap();
+        assert_eq!(straddling, bytes[WINDOW - 4..WINDOW + 4]);
+    }
+
+    #[test]
+    fn changes_survive_moving_the_window() {
+        let mut buffer = HugeBuffer::new();
+        buffer.write_at(0, &[1; 2 * WINDOW]).unwrap();
+
+        buffer.write_at(WINDOW as u32 - 1, &[2, 3]).unwrap();
+        buffer.write_at(0, &[4]).unwrap();
+        buffer.flush().unwrap();
+
+        let bytes = contents(&mut buffer);
+        assert_eq!(bytes[..2], [4, 1]);
+        assert_eq!(bytes[WINDOW - 2..WINDOW + 2], [1, 2, 3, 1]);
+    }
+
+    #[test]
+    fn writing_past_the_end_fills_with_zeros() {
+        let mut buffer = HugeBuffer::new();
+        buffer.write_at(0, &[1, 2]).unwrap();
+        buffer.write_at(WINDOW as u32 + 10, &[3]).unwrap();
+
+        let bytes = contents(&mut buffer);
+        assert_eq!(bytes.len(), WINDOW + 11);
+        assert_eq!(bytes[..2], [1, 2]);
+        assert!(bytes[2..WINDOW + 10].iter().all(|&byte| byte == 0));
+        assert_eq!(bytes[WINDOW + 10], 3);
+    }
+
+    #[test]
+    fn truncated_bytes_are_zeroed_when_extended_again() {
+        let mut buffer = HugeBuffer::new();
+        buffer.write_at(0, &[1; 16]).unwrap();
+        buffer.truncate(4);
+        buffer.write_at(8, &[2]).unwrap();
+
+        assert_eq!(contents(&mut buffer), [1, 1, 1, 1, 0, 0, 0, 0, 2]);
+    }
+
+    #[test]
+    fn read_at_past_the_end_fails() {
+        let mut buffer = HugeBuffer::new();
+        buffer.write_at(0, &[1; 4]).unwrap();
+
+        assert_eq!(buffer.read_at(2, &mut [0; 3]).unwrap_err().kind(), ErrorKind::UnexpectedEof);
+        assert!(buffer.read_at(4, &mut []).is_ok());
+    }
+
+    #[test]
+    fn cursor_reads_writes_and_seeks() {
+        let mut buffer = HugeBuffer::new();
+        buffer.write_all(b"hello, world").unwrap();
+        assert_eq!(buffer.seek(SeekFrom::Current(-5)).unwrap(), 7);
+
+        let mut bytes = [0; 8];
+        assert_eq!(buffer.read(&mut bytes).unwrap(), 5);
+        assert_eq!(bytes[..5], *b"world");
+
+        assert_eq!(buffer.seek(SeekFrom::End(-12)).unwrap(), 0);
+        buffer.write_all(b"HELLO").unwrap();
+        buffer.seek(SeekFrom::Start(0)).unwrap();
+        assert_eq!(buffer.read(&mut bytes).unwrap(), 8);
+        assert_eq!(bytes, *b"HELLO, w");
+
+        assert!(buffer.seek(SeekFrom::Current(-100)).is_err());
+    }
+
+    #[test]
+    fn read_past_the_end_returns_nothing() {
+        let mut buffer = HugeBuffer::new();
+        buffer.write_all(&[1; 4]).unwrap();
+
+        buffer.seek(SeekFrom::Start(4)).unwrap();
+        assert_eq!(buffer.read(&mut [0; 4]).unwrap(), 0);
+
+        // Even beyond where offsets fit in 32 bits.
+        buffer.seek(SeekFrom::Start(u64::MAX)).unwrap();
+        assert_eq!(buffer.read(&mut [0; 4]).unwrap(), 0);
+        assert_eq!(buffer.write(&[1]).unwrap_err().kind(), ErrorKind::OutOfMemory);
+    }
+
+    #[test]
+    fn vec_push_pop_and_iterate() {
+        let mut vec = HugeVec::<u32>::new();
+
+        for value in 0..1000 {
+            vec.push(value * 3).unwrap();
+        }
+
+        assert_eq!(vec.len(), 1000);
+        assert_eq!(vec.get(999).unwrap(), Some(2997));
+        assert_eq!(vec.get(1000).unwrap(), None);
+
+        vec.set(500, 7).unwrap();
+        assert_eq!(vec.iter().map(Result::unwrap).filter(|&value| value == 7).count(), 1);
+        assert!(vec.set(1000, 0).is_err());
+
+        assert_eq!(vec.pop().unwrap(), Some(2997));
+        assert_eq!(vec.len(), 999);
+    }
+}
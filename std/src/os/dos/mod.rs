@@ -0,0 +1,6 @@
+//! DOS-specific extensions to the primitives in the `std` crate.
+
+pub mod alloc;
+pub mod fs;
+pub mod huge;
+pub mod time;
@@ -0,0 +1,23 @@
+//! DOS-specific extensions to primitives in the [`std::time`] module.
+//!
+//! [`std::time`]: crate::time
+
+use crate::io::{self, ErrorKind};
+use crate::time::{DateTime, SystemTime};
+
+/// Sets the system clock to `time`, taken as local time like [`SystemTime::now`] does.
+///
+/// Both the DOS clock and the real-time clock are set, so the change survives a reboot.
+/// Machines without a real-time clock only have their DOS clock set.
+pub fn set_system_time(time: SystemTime) -> io::Result<()> {
+    let datetime = DateTime::try_from(time).map_err(|_| io::Error::new_const(ErrorKind::InvalidInput, "time out of range"))?;
+
+    dos::time::set_date(datetime.year(), datetime.month(), datetime.day())?;
+    dos::time::set_time(datetime.hour(), datetime.minute(), datetime.second(), datetime.hundredths())?;
+
+    // The XT has no real-time clock, which is no reason to fail once DOS has the new time.
+    let _ = bios::rtc::set_date(datetime.year(), datetime.month(), datetime.day())
+        .and_then(|()| bios::rtc::set_time(datetime.hour(), datetime.minute(), datetime.second()));
+
+    Ok(())
+}
@@ -0,0 +1,3 @@
+//! OS-specific functionality.
+
+pub mod dos;
@@ -1 +1,208 @@
+use crate::bstr::ByteStr;
+use crate::io::{self, ErrorKind};
+use crate::sys::fs::MAX_PATH;
+use core::ffi::CStr;
+use core::fmt::{self, Debug, Display};
+
 pub struct Path {}
+
+/// An owned DOS path of at most `MAX_PATH - 1` bytes.
+///
+/// The path is kept NUL-terminated, so [`PathBuf::as_ptr`] can be passed to any function
+/// taking a path.
+#[derive(Clone, Copy, PartialEq, Eq, Hash)]
+pub struct PathBuf {
+    buf: [u8; MAX_PATH],
+    len: usize,
+}
+
+impl PathBuf {
+    /// Creates an empty path.
+    #[inline]
+    #[must_use]
+    pub const fn new() -> Self {
+        Self { buf: [0; MAX_PATH], len: 0 }
+    }
+
+    /// Copies the NUL-terminated path at `path`.
+    pub fn from_ptr(path: *const u8) -> io::Result<Self> {
+        let mut buf = Self::new();
+        buf.push(unsafe { CStr::from_ptr(path.cast()) }.to_bytes())?;
+        Ok(buf)
+    }
+
+    /// Returns the path without the terminating NUL.
+    #[inline]
+    #[must_use]
+    pub fn as_bytes(&self) -> &[u8] {
+        &self.buf[..self.len]
+    }
+
+    /// Returns a pointer to the NUL-terminated path.
+    #[inline]
+    #[must_use]
+    pub const fn as_ptr(&self) -> *const u8 {
+        self.buf.as_ptr()
+    }
+
+    /// Extends `self` with `path`, inserting a backslash if needed.
+    ///
+    /// If `path` is absolute or has a drive letter, it replaces `self`. A bare drive such as
+    /// `C:` stands for the current directory of that drive, so no backslash follows it. Fails,
+    /// leaving `self` unchanged, if the result would not fit.
+    pub fn push(&mut self, path: &[u8]) -> io::Result<()> {
+        let len = if matches!(path, [b'\\' | b'/', ..] | [_, b':', ..]) { 0 } else { self.len };
+        let separator = len > 0 && !path.is_empty() && !is_separator(self.buf[len - 1]) && !matches!(self.buf[..len], [_, b':']);
+        let end = len + separator as usize + path.len();
+
+        if end >= MAX_PATH {
+            return Err(io::Error::new_const(ErrorKind::InvalidInput, "path too long"));
+        }
+
+        if separator {
+            self.buf[len] = b'\\';
+        }
+
+        self.buf[end - path.len()..end].copy_from_slice(path);
+        self.buf[end] = 0;
+        self.len = end;
+        Ok(())
+    }
+
+    /// Truncates `self` to its parent.
+    ///
+    /// Returns `false` and does nothing if there is no parent, as for `C:\` or the empty path.
+    pub fn pop(&mut self) -> bool {
+        let root = self.root();
+
+        if self.len <= root {
+            return false;
+        }
+
+        let parent = match self.as_bytes()[root..].iter().rposition(|&byte| is_separator(byte)) {
+            Some(index) => root + index,
+            None => root,
+        };
+
+        self.buf[parent] = 0;
+        self.len = parent;
+        true
+    }
+
+    /// Removes trailing separators, keeping the one of a root directory such as `C:\`.
+    ///
+    /// DOS does not find `C:\DOS\`, only `C:\DOS`.
+    pub fn trim_end_separators(&mut self) {
+        let root = self.root();
+
+        while self.len > root && is_separator(self.buf[self.len - 1]) {
+            self.len -= 1;
+        }
+
+        self.buf[self.len] = 0;
+    }
+
+    /// Returns the length of the drive and root directory the path starts with, as in `C:\`.
+    fn root(&self) -> usize {
+        let prefix = if matches!(self.as_bytes(), [_, b':', ..]) { 2 } else { 0 };
+        prefix + self.as_bytes()[prefix..].first().is_some_and(|&byte| is_separator(byte)) as usize
+    }
+}
+
+impl Default for PathBuf {
+    #[inline]
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Debug for PathBuf {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        Debug::fmt(ByteStr::new(self.as_bytes()), f)
+    }
+}
+
+impl Display for PathBuf {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        Display::fmt(ByteStr::new(self.as_bytes()), f)
+    }
+}
+
+/// Returns `true` if `byte` separates path components. DOS accepts both slashes.
+#[inline]
+#[must_use]
+pub const fn is_separator(byte: u8) -> bool {
+    matches!(byte, b'\\' | b'/')
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn path(bytes: &[u8]) -> PathBuf {
+        let mut path = PathBuf::new();
+        path.push(bytes).unwrap();
+        path
+    }
+
+    #[test]
+    fn push_inserts_a_backslash() {
+        let mut dir = path(b"C:\\DOS");
+        dir.push(b"*.*").unwrap();
+        assert_eq!(dir.as_bytes(), b"C:\\DOS\\*.*");
+
+        let mut root = path(b"C:\\");
+        root.push(b"*.*").unwrap();
+        assert_eq!(root.as_bytes(), b"C:\\*.*");
+    }
+
+    #[test]
+    fn push_keeps_a_bare_drive_relative() {
+        let mut drive = path(b"C:");
+        drive.push(b"*.*").unwrap();
+        assert_eq!(drive.as_bytes(), b"C:*.*");
+    }
+
+    #[test]
+    fn push_replaces_with_an_absolute_path() {
+        let mut dir = path(b"C:\\DOS");
+        dir.push(b"D:GAMES").unwrap();
+        assert_eq!(dir.as_bytes(), b"D:GAMES");
+
+        dir.push(b"\\TEMP").unwrap();
+        assert_eq!(dir.as_bytes(), b"\\TEMP");
+    }
+
+    #[test]
+    fn push_fails_when_too_long() {
+        let mut dir = path(b"C:\\");
+        assert!(dir.push(&[b'A'; MAX_PATH]).is_err());
+        assert_eq!(dir.as_bytes(), b"C:\\");
+    }
+
+    #[test]
+    fn pop_stops_at_the_root() {
+        let mut dir = path(b"C:\\DOS\\BIN");
+        assert!(dir.pop());
+        assert_eq!(dir.as_bytes(), b"C:\\DOS");
+        assert!(dir.pop());
+        assert_eq!(dir.as_bytes(), b"C:\\");
+        assert!(!dir.pop());
+    }
+
+    #[test]
+    fn trim_end_separators_keeps_the_root() {
+        let mut dir = path(b"C:\\DOS\\/");
+        dir.trim_end_separators();
+        assert_eq!(dir.as_bytes(), b"C:\\DOS");
+        assert_eq!(unsafe { *dir.as_ptr().add(dir.as_bytes().len()) }, 0);
+
+        let mut root = path(b"C:\\");
+        root.trim_end_separators();
+        assert_eq!(root.as_bytes(), b"C:\\");
+
+        let mut drive = path(b"C:");
+        drive.trim_end_separators();
+        assert_eq!(drive.as_bytes(), b"C:");
+    }
+}
@@ -1,3 +1,11 @@
 pub fn abort() -> ! {
     todo!()
 }
+
+/// Terminates the current process with the specified exit code.
+///
+/// Only the low byte of `code` reaches DOS. Hooks installed through the `dos` crate, such
+/// as a sped up timer, are undone first, but destructors on the stack do not run.
+pub fn exit(code: i32) -> ! {
+    dos::process::exit(code as u8)
+}
@@ -11,9 +11,7 @@ impl RandomSource for DefaultRandomSource {
     #[inline(never)]
     fn fill_bytes(&mut self, bytes: &mut [u8]) {
         unsafe {
-            let mut cx: u16;
-            let mut dx: u16;
-            asm!("int 0x1A", in("ah") 0u8, out("cx") cx, out("dx") dx, options(nomem, nostack));
+            let ticks = bios::rtc::ticks();
 
             let mut pit: u8;
             asm!("in al, dx", in("dx") 0x40u16, out("al") pit, options(nomem, nostack));
@@ -24,7 +22,7 @@ impl RandomSource for DefaultRandomSource {
 
             let phys_addr: u32 = ((segment as u32) << 4) | (offset as u32);
 
-            let mut state: u32 = ((cx as u32) << 16) ^ (dx as u32) ^ (pit as u32) ^ phys_addr;
+            let mut state: u32 = ticks ^ (pit as u32) ^ phys_addr;
 
             for chunk in bytes.chunks_mut(4) {
                 state ^= state << 13;
@@ -0,0 +1,135 @@
+use super::holes::{self, GRANULE, Hole};
+use crate::alloc::{GlobalAlloc, Layout, System};
+use core::ptr::{self, null_mut};
+use dos::memory::{self, PARAGRAPH};
+
+/// Tells the runtime to move the stack below the heap, so the blocks DOS allocates land in our
+/// segment.
+#[unsafe(no_mangle)]
+static __dos_block_heap: bool = true;
+
+/// Allocations from this size up get a DOS block of their own, which `realloc` can resize in place.
+const LARGE: usize = 4096;
+
+/// The size of the DOS blocks that small allocations are carved from.
+const ARENA: usize = 4096;
+
+/// The bytes our pointers can reach, the offsets into [`memory::data_segment`].
+const WINDOW: usize = 0x10000;
+
+/// The most blocks below our segment [`allocate_block`] skips before giving up.
+const SKIPPED: usize = 16;
+
+/// The free ranges of all arenas, sorted by address. Arenas are never given back to DOS.
+static mut HOLES: *mut Hole = null_mut();
+
+unsafe impl GlobalAlloc for System {
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        if is_large(layout) {
+            return allocate_block(layout.size()).map_or(null_mut(), |(pointer, _)| pointer);
+        }
+
+        let size = holes::size(layout);
+        let align = layout.align().max(GRANULE);
+
+        loop {
+            if let Some(pointer) = unsafe { holes::take(&raw mut HOLES, size, align) } {
+                return pointer;
+            }
+
+            // Room for the worst case alignment padding, which becomes a hole of its own.
+            let Some((arena, size)) = allocate_block((size + align - GRANULE).max(ARENA)) else {
+                return null_mut();
+            };
+
+            unsafe { holes::insert(&raw mut HOLES, arena, size) };
+        }
+    }
+
+    unsafe fn dealloc(&self, pointer: *mut u8, layout: Layout) {
+        if is_large(layout) {
+            let _ = unsafe { memory::free(segment_of(pointer)) };
+        } else {
+            unsafe { holes::insert(&raw mut HOLES, pointer, holes::size(layout)) };
+        }
+    }
+
+    unsafe fn realloc(&self, pointer: *mut u8, layout: Layout, new_size: usize) -> *mut u8 {
+        let new_layout = unsafe { Layout::from_size_align_unchecked(new_size, layout.align()) };
+
+        // Blocks of their own grow and shrink in place if DOS has room.
+        if is_large(layout) && is_large(new_layout) && (pointer as usize) + new_size <= WINDOW {
+            let paragraphs = new_size.div_ceil(PARAGRAPH) as u16;
+
+            if unsafe { memory::resize(segment_of(pointer), paragraphs) }.is_ok() {
+                return pointer;
+            }
+        }
+
+        let new_pointer = unsafe { self.alloc(new_layout) };
+
+        if !new_pointer.is_null() {
+            unsafe {
+                ptr::copy_nonoverlapping(pointer, new_pointer, layout.size().min(new_size));
+                self.dealloc(pointer, layout);
+            }
+        }
+
+        new_pointer
+    }
+}
+
+/// Returns `true` if `layout` gets a DOS block of its own. Blocks start on a paragraph, so
+/// stricter alignments go to the arenas.
+#[inline]
+const fn is_large(layout: Layout) -> bool {
+    layout.size() >= LARGE && layout.align() <= PARAGRAPH
+}
+
+/// Returns the segment of the DOS block starting at `pointer`.
+#[inline]
+fn segment_of(pointer: *mut u8) -> u16 {
+    memory::data_segment() + (pointer as usize / PARAGRAPH) as u16
+}
+
+/// Allocates a DOS block of at least `size` bytes, returning a pointer to it and its size.
+///
+/// DOS allocates first fit, so it may hand out a block below our segment, such as a freed
+/// environment or a hole left by a TSR. Those are kept allocated while asking again, so DOS
+/// moves on to the next free block, and are given back once one lands in our segment. Blocks
+/// beyond it are given back right away, so this fails once the memory left in our segment runs
+/// out, even if DOS has more above it.
+fn allocate_block(size: usize) -> Option<(*mut u8, usize)> {
+    let paragraphs = u16::try_from(size.div_ceil(PARAGRAPH)).ok()?;
+    let size = paragraphs as usize * PARAGRAPH;
+    let data_segment = memory::data_segment();
+
+    let mut skipped = [0; SKIPPED];
+    let mut count = 0;
+
+    let block = loop {
+        let Ok(segment) = memory::allocate(paragraphs) else { break None };
+
+        if segment >= data_segment {
+            let offset = (segment - data_segment) as usize * PARAGRAPH;
+
+            if offset + size <= WINDOW {
+                break Some((offset as *mut u8, size));
+            }
+        } else if count < SKIPPED {
+            skipped[count] = segment;
+            count += 1;
+            continue;
+        }
+
+        // DOS hands out the lowest block that fits, so none of the rest are any closer.
+        let _ = unsafe { memory::free(segment) };
+        break None;
+    };
+
+    for &segment in &skipped[..count] {
+        let _ = unsafe { memory::free(segment) };
+    }
+
+    block
+}
@@ -0,0 +1,175 @@
+//! A free list of holes sorted by address, shared by the allocators.
+
+use crate::alloc::Layout;
+use core::ptr::null_mut;
+
+/// A free range, stored at the start of the range itself.
+pub struct Hole {
+    size: usize,
+    next: *mut Hole,
+}
+
+/// The granularity of allocations, so that every freed range can hold a [`Hole`].
+pub const GRANULE: usize = size_of::<Hole>();
+
+/// Returns the bytes an allocation of `layout` takes.
+#[inline]
+pub const fn size(layout: Layout) -> usize {
+    if layout.size() < GRANULE { GRANULE } else { layout.size().next_multiple_of(GRANULE) }
+}
+
+/// Takes `size` bytes aligned to `align` from the first hole in `list` they fit in. Whatever is left on
+/// either side stays a hole.
+pub unsafe fn take(list: *mut *mut Hole, size: usize, align: usize) -> Option<*mut u8> {
+    let mut link = list;
+
+    unsafe {
+        while !(*link).is_null() {
+            let hole = *link;
+            let start = hole as usize;
+            let end = start + (*hole).size;
+            let aligned = start.next_multiple_of(align);
+
+            if aligned + size <= end {
+                let mut rest = (*hole).next;
+
+                if aligned + size < end {
+                    let tail = (aligned + size) as *mut Hole;
+                    tail.write(Hole { size: end - aligned - size, next: rest });
+                    rest = tail;
+                }
+
+                if aligned > start {
+                    (*hole).size = aligned - start;
+                    (*hole).next = rest;
+                } else {
+                    *link = rest;
+                }
+
+                return Some(aligned as *mut u8);
+            }
+
+            link = &raw mut (*hole).next;
+        }
+    }
+
+    None
+}
+
+/// Returns `size` bytes at `pointer` to `list`, merging them with adjacent holes.
+pub unsafe fn insert(list: *mut *mut Hole, pointer: *mut u8, size: usize) {
+    let start = pointer as usize;
+    let mut link = list;
+    let mut previous: *mut Hole = null_mut();
+
+    unsafe {
+        while !(*link).is_null() && (*link as usize) < start {
+            previous = *link;
+            link = &raw mut (*previous).next;
+        }
+
+        let next = *link;
+        let hole = pointer.cast::<Hole>();
+        hole.write(Hole { size, next });
+        *link = hole;
+
+        if !next.is_null() && start + size == next as usize {
+            (*hole).size += (*next).size;
+            (*hole).next = (*next).next;
+        }
+
+        if !previous.is_null() && previous as usize + (*previous).size == start {
+            (*previous).size += (*hole).size;
+            (*previous).next = (*hole).next;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use alloc_crate::vec::Vec;
+
+    #[repr(C, align(64))]
+    struct Arena([u8; 512]);
+
+    /// Returns the holes in `list` as offsets from `base` and sizes.
+    fn holes(list: *mut Hole, base: *mut u8) -> Vec<(usize, usize)> {
+        let mut holes = Vec::new();
+        let mut hole = list;
+
+        while !hole.is_null() {
+            unsafe {
+                holes.push((hole as usize - base as usize, (*hole).size));
+                hole = (*hole).next;
+            }
+        }
+
+        holes
+    }
+
+    #[test]
+    fn size_rounds_to_granules() {
+        assert_eq!(size(Layout::from_size_align(0, 1).unwrap()), GRANULE);
+        assert_eq!(size(Layout::from_size_align(1, 1).unwrap()), GRANULE);
+        assert_eq!(size(Layout::from_size_align(GRANULE, 1).unwrap()), GRANULE);
+        assert_eq!(size(Layout::from_size_align(GRANULE + 1, 1).unwrap()), 2 * GRANULE);
+    }
+
+    #[test]
+    fn take_splits_and_aligns() {
+        let mut arena = Arena([0; 512]);
+        let base = arena.0.as_mut_ptr();
+        let mut list = null_mut();
+
+        unsafe {
+            insert(&raw mut list, base, 512);
+
+            assert_eq!(take(&raw mut list, 2 * GRANULE, GRANULE), Some(base));
+            assert_eq!(holes(list, base), [(2 * GRANULE, 512 - 2 * GRANULE)]);
+
+            // The padding before an aligned allocation stays a hole.
+            assert_eq!(take(&raw mut list, GRANULE, 64), Some(base.add(64)));
+            assert_eq!(holes(list, base), [(2 * GRANULE, 64 - 2 * GRANULE), (64 + GRANULE, 448 - GRANULE)]);
+
+            assert_eq!(take(&raw mut list, 512, GRANULE), None);
+        }
+    }
+
+    #[test]
+    fn take_uses_up_an_exact_fit() {
+        let mut arena = Arena([0; 512]);
+        let base = arena.0.as_mut_ptr();
+        let mut list = null_mut();
+
+        unsafe {
+            insert(&raw mut list, base, 512);
+            assert_eq!(take(&raw mut list, 512, GRANULE), Some(base));
+            assert!(list.is_null());
+        }
+    }
+
+    #[test]
+    fn insert_sorts_and_merges() {
+        let mut arena = Arena([0; 512]);
+        let base = arena.0.as_mut_ptr();
+        let mut list = null_mut();
+        let chunk = 4 * GRANULE;
+
+        unsafe {
+            insert(&raw mut list, base.add(2 * chunk), chunk);
+            insert(&raw mut list, base, chunk);
+            assert_eq!(holes(list, base), [(0, chunk), (2 * chunk, chunk)]);
+
+            // Filling the gap merges with both neighbours.
+            insert(&raw mut list, base.add(chunk), chunk);
+            assert_eq!(holes(list, base), [(0, 3 * chunk)]);
+
+            insert(&raw mut list, base.add(3 * chunk), chunk);
+            assert_eq!(holes(list, base), [(0, 4 * chunk)]);
+
+            insert(&raw mut list, base.add(6 * chunk), chunk);
+            assert_eq!(holes(list, base), [(0, 4 * chunk), (6 * chunk, chunk)]);
+        }
+    }
+}
@@ -0,0 +1,22 @@
+mod holes;
+
+cfg_select! {
+    feature = "near_heap" => {
+        mod near;
+    }
+    _ => {
+        mod dos;
+    }
+}
+
+// The memory the runtime left for the heap at startup.
+unsafe extern "C" {
+    static __dos_heap_start: u32;
+    static __dos_heap_end: u32;
+}
+
+/// Returns the bytes the runtime left for the heap at startup.
+#[inline]
+pub fn heap_size() -> usize {
+    unsafe { (__dos_heap_end - __dos_heap_start) as usize }
+}
@@ -0,0 +1,97 @@
+use super::holes::{self, GRANULE, Hole};
+use super::{__dos_heap_end, __dos_heap_start};
+use crate::alloc::{GlobalAlloc, Layout, System};
+use core::arch::asm;
+use core::ptr::null_mut;
+
+/// Tells the runtime to keep the memory block at full size and put the stack at its top.
+#[unsafe(no_mangle)]
+static __dos_near_heap: bool = true;
+
+/// Room kept free below the stack pointer when the heap grows, on top of the size of the
+/// stack the heap already stops short of. Interrupt handlers, ours and those of DOS, the BIOS
+/// and resident programs, push onto whatever stack they interrupt, and a deeper call chain
+/// than the one allocating has to fit as well.
+const STACK_MARGIN: usize = 2048;
+
+/// The freed ranges below [`BREAK`], sorted by address.
+static mut HOLES: *mut Hole = null_mut();
+
+/// The end of the part of the heap handed out so far, or `0` before the first allocation.
+static mut BREAK: usize = 0;
+
+unsafe impl GlobalAlloc for System {
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        let size = holes::size(layout);
+        let align = layout.align().max(GRANULE);
+
+        if let Some(pointer) = unsafe { holes::take(&raw mut HOLES, size, align) } {
+            return pointer;
+        }
+
+        let start = heap_break();
+        let aligned = start.next_multiple_of(align);
+
+        if !unsafe { set_break(aligned + size) } {
+            return null_mut();
+        }
+
+        if aligned > start {
+            unsafe { holes::insert(&raw mut HOLES, start as *mut u8, aligned - start) };
+        }
+
+        aligned as *mut u8
+    }
+
+    unsafe fn dealloc(&self, pointer: *mut u8, layout: Layout) {
+        unsafe { holes::insert(&raw mut HOLES, pointer, holes::size(layout)) };
+    }
+
+    unsafe fn realloc(&self, pointer: *mut u8, layout: Layout, new_size: usize) -> *mut u8 {
+        let new_layout = unsafe { Layout::from_size_align_unchecked(new_size, layout.align()) };
+
+        // The last allocation grows and shrinks in place by moving the break.
+        if pointer as usize + holes::size(layout) == heap_break() && unsafe { set_break(pointer as usize + holes::size(new_layout)) } {
+            return pointer;
+        }
+
+        let new_pointer = unsafe { self.alloc(new_layout) };
+
+        if !new_pointer.is_null() {
+            unsafe {
+                core::ptr::copy_nonoverlapping(pointer, new_pointer, layout.size().min(new_size));
+                self.dealloc(pointer, layout);
+            }
+        }
+
+        new_pointer
+    }
+}
+
+/// Returns the end of the part of the heap handed out so far.
+#[inline]
+fn heap_break() -> usize {
+    match unsafe { BREAK } {
+        0 => unsafe { __dos_heap_start as usize },
+        end => end,
+    }
+}
+
+/// Moves the end of the heap to `end`. Fails, leaving it where it was, if the heap would run
+/// past the memory set aside for it or to within [`STACK_MARGIN`] of the stack pointer as it
+/// is now. Nothing guards the stack afterwards.
+unsafe fn set_break(end: usize) -> bool {
+    if end > unsafe { __dos_heap_end as usize } || end + STACK_MARGIN > stack_pointer() {
+        return false;
+    }
+
+    unsafe { BREAK = end };
+    true
+}
+
+#[inline]
+fn stack_pointer() -> usize {
+    let pointer: usize;
+    unsafe { asm!("mov {0:e}, esp", out(reg) pointer, options(nomem, nostack, preserves_flags)) };
+    pointer
+}
@@ -1 +1,511 @@
+use crate::io::{self, ErrorKind, SeekFrom};
+use crate::path::{PathBuf, is_separator};
+use crate::time::{FileTime, SystemTime};
+use dos::fs::{Access, Flags};
 
+/// The longest path DOS accepts, including the terminating NUL.
+pub const MAX_PATH: usize = 128;
+
+#[derive(Debug)]
+pub struct File {
+    inner: dos::fs::File,
+    append: bool,
+}
+
+#[derive(Debug, Default, Clone)]
+pub struct OpenOptions {
+    append: bool,
+    create: bool,
+    create_new: bool,
+    read: bool,
+    truncate: bool,
+    write: bool,
+}
+
+/// How [`File::open`] treats a missing or existing file.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Creation {
+    OpenExisting,
+    OpenAlways,
+    TruncateExisting,
+    CreateAlways,
+    CreateNew,
+}
+
+impl OpenOptions {
+    #[inline]
+    pub const fn new() -> Self {
+        Self { append: false, create: false, create_new: false, read: false, truncate: false, write: false }
+    }
+
+    #[inline]
+    pub const fn append(&mut self, append: bool) {
+        self.append = append;
+    }
+
+    #[inline]
+    pub const fn create(&mut self, create: bool) {
+        self.create = create;
+    }
+
+    #[inline]
+    pub const fn create_new(&mut self, create_new: bool) {
+        self.create_new = create_new;
+    }
+
+    #[inline]
+    pub const fn read(&mut self, read: bool) {
+        self.read = read;
+    }
+
+    #[inline]
+    pub const fn truncate(&mut self, truncate: bool) {
+        self.truncate = truncate;
+    }
+
+    #[inline]
+    pub const fn write(&mut self, write: bool) {
+        self.write = write;
+    }
+
+    fn access(&self) -> io::Result<Access> {
+        match (self.read, self.write || self.append) {
+            (true, false) => Ok(Access::Read),
+            (false, true) => Ok(Access::Write),
+            (true, true) => Ok(Access::ReadWrite),
+            (false, false) => Err(io::Error::from(ErrorKind::InvalidInput)),
+        }
+    }
+
+    fn creation(&self) -> io::Result<Creation> {
+        match (self.write, self.append) {
+            (true, false) => {}
+            (false, false) => {
+                if self.truncate || self.create || self.create_new {
+                    return Err(io::Error::from(ErrorKind::InvalidInput));
+                }
+            }
+            (_, true) => {
+                if self.truncate && !self.create_new {
+                    return Err(io::Error::from(ErrorKind::InvalidInput));
+                }
+            }
+        }
+
+        Ok(match (self.create, self.truncate, self.create_new) {
+            (false, false, false) => Creation::OpenExisting,
+            (true, false, false) => Creation::OpenAlways,
+            (false, true, false) => Creation::TruncateExisting,
+            (true, true, false) => Creation::CreateAlways,
+            (_, _, true) => Creation::CreateNew,
+        })
+    }
+}
+
+impl File {
+    pub fn open(path: *const u8, opts: &OpenOptions) -> io::Result<Self> {
+        let access = opts.access()?;
+
+        let inner = match opts.creation()? {
+            Creation::OpenExisting => dos::fs::File::open(path, access)?,
+            Creation::OpenAlways => match dos::fs::File::open(path, access) {
+                Err(dos::Error::FileNotFound) => reopen(dos::fs::File::create_new(path, Flags::empty())?, path, access)?,
+                result => result?,
+            },
+            Creation::TruncateExisting => {
+                let file = dos::fs::File::open(path, access)?;
+                file.truncate()?;
+                file
+            }
+            Creation::CreateAlways => reopen(dos::fs::File::create(path, Flags::empty())?, path, access)?,
+            Creation::CreateNew => reopen(dos::fs::File::create_new(path, Flags::empty())?, path, access)?,
+        };
+
+        let file = Self { inner, append: opts.append };
+        if file.append {
+            file.inner.seek(dos::fs::SeekFrom::End(0))?;
+        }
+
+        Ok(file)
+    }
+
+    #[inline]
+    pub fn read(&self, buf: &mut [u8]) -> io::Result<usize> {
+        Ok(self.inner.read(buf)?)
+    }
+
+    pub fn write(&self, buf: &[u8]) -> io::Result<usize> {
+        if self.append {
+            self.inner.seek(dos::fs::SeekFrom::End(0))?;
+        }
+
+        Ok(self.inner.write(buf)?)
+    }
+
+    #[inline]
+    pub fn flush(&self) -> io::Result<()> {
+        Ok(())
+    }
+
+    /// Returns the size and timestamps of the open file.
+    ///
+    /// DOS cannot return the attributes of an open file, so they are reported as empty.
+    pub fn file_attr(&self) -> io::Result<FileAttr> {
+        let position = self.inner.seek(dos::fs::SeekFrom::Current(0))?;
+        let size = self.inner.seek(dos::fs::SeekFrom::End(0));
+        self.inner.seek(dos::fs::SeekFrom::Start(position))?;
+
+        Ok(FileAttr {
+            size: size?,
+            attributes: Flags::empty(),
+            modified: self.inner.modified()?,
+            accessed: self.inner.accessed().ok(),
+            created: self.inner.created().ok(),
+        })
+    }
+
+    pub fn set_times(&self, times: FileTimes) -> io::Result<()> {
+        if let Some(time) = times.modified {
+            self.inner.set_modified(file_time(time)?)?;
+        }
+
+        if let Some(time) = times.accessed {
+            self.inner.set_accessed(file_time(time)?)?;
+        }
+
+        if let Some(time) = times.created {
+            self.inner.set_created(file_time(time)?)?;
+        }
+
+        Ok(())
+    }
+
+    pub fn seek(&self, pos: SeekFrom) -> io::Result<u64> {
+        const OUT_OF_RANGE: io::Error = io::Error::new_const(ErrorKind::InvalidInput, "seek offset out of range");
+
+        let pos = match pos {
+            SeekFrom::Start(offset) => dos::fs::SeekFrom::Start(u32::try_from(offset).map_err(|_| OUT_OF_RANGE)?),
+            SeekFrom::End(offset) => dos::fs::SeekFrom::End(i32::try_from(offset).map_err(|_| OUT_OF_RANGE)?),
+            SeekFrom::Current(offset) => dos::fs::SeekFrom::Current(i32::try_from(offset).map_err(|_| OUT_OF_RANGE)?),
+        };
+
+        Ok(self.inner.seek(pos)?.into())
+    }
+}
+
+/// Files created by DOS are always opened read-write, so reopen them if a narrower access mode was requested.
+fn reopen(file: dos::fs::File, path: *const u8, access: Access) -> dos::fs::Result<dos::fs::File> {
+    if access == Access::ReadWrite {
+        return Ok(file);
+    }
+
+    file.close()?;
+    dos::fs::File::open(path, access)
+}
+
+pub struct ReadDir {
+    inner: dos::fs::ReadDir,
+}
+
+#[derive(Debug, Clone)]
+pub struct DirEntry {
+    inner: dos::fs::DirEntry,
+}
+
+#[derive(Debug, Clone)]
+pub struct FileAttr {
+    size: u32,
+    attributes: Flags,
+    modified: FileTime,
+    accessed: Option<FileTime>,
+    created: Option<FileTime>,
+}
+
+#[derive(Debug, Default, Clone, Copy)]
+pub struct FileTimes {
+    accessed: Option<SystemTime>,
+    modified: Option<SystemTime>,
+    created: Option<SystemTime>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct FilePermissions {
+    attributes: Flags,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct FileType {
+    attributes: Flags,
+}
+
+impl Iterator for ReadDir {
+    type Item = io::Result<DirEntry>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        for entry in self.inner.by_ref() {
+            match entry {
+                Ok(entry) if matches!(entry.name(), b"." | b"..") => {}
+                Ok(inner) => return Some(Ok(DirEntry { inner })),
+                Err(error) => return Some(Err(error.into())),
+            }
+        }
+
+        None
+    }
+}
+
+impl DirEntry {
+    #[inline]
+    pub fn file_name(&self) -> &[u8] {
+        self.inner.name()
+    }
+
+    #[inline]
+    pub fn file_type(&self) -> io::Result<FileType> {
+        Ok(FileType { attributes: self.inner.flags() })
+    }
+
+    #[inline]
+    pub fn metadata(&self) -> io::Result<FileAttr> {
+        Ok(FileAttr::from(&self.inner))
+    }
+}
+
+impl From<&dos::fs::DirEntry> for FileAttr {
+    #[inline]
+    fn from(entry: &dos::fs::DirEntry) -> Self {
+        Self { size: entry.size(), attributes: entry.flags(), modified: entry.modified(), accessed: None, created: None }
+    }
+}
+
+impl FileAttr {
+    #[inline]
+    pub fn size(&self) -> u64 {
+        self.size.into()
+    }
+
+    #[inline]
+    pub fn file_type(&self) -> FileType {
+        FileType { attributes: self.attributes }
+    }
+
+    #[inline]
+    pub fn modified(&self) -> io::Result<SystemTime> {
+        Ok(self.modified.into())
+    }
+
+    #[inline]
+    pub fn accessed(&self) -> io::Result<SystemTime> {
+        self.accessed.map(SystemTime::from).ok_or(io::Error::new_const(ErrorKind::Unsupported, "last access time not available"))
+    }
+
+    #[inline]
+    pub fn created(&self) -> io::Result<SystemTime> {
+        self.created.map(SystemTime::from).ok_or(io::Error::new_const(ErrorKind::Unsupported, "creation time not available"))
+    }
+
+    #[inline]
+    pub fn attributes(&self) -> Flags {
+        self.attributes
+    }
+
+    #[inline]
+    pub fn perm(&self) -> FilePermissions {
+        FilePermissions { attributes: self.attributes }
+    }
+}
+
+impl FileTimes {
+    #[inline]
+    pub fn set_accessed(&mut self, time: SystemTime) {
+        self.accessed = Some(time);
+    }
+
+    #[inline]
+    pub fn set_modified(&mut self, time: SystemTime) {
+        self.modified = Some(time);
+    }
+
+    #[inline]
+    pub fn set_created(&mut self, time: SystemTime) {
+        self.created = Some(time);
+    }
+}
+
+/// Converts `time` to a file timestamp, rounding down to two seconds.
+fn file_time(time: SystemTime) -> io::Result<FileTime> {
+    time.duration_since(SystemTime::UNIX_EPOCH)
+        .ok()
+        .and_then(|since| FileTime::from_unix(since.as_secs()))
+        .ok_or(io::Error::new_const(ErrorKind::InvalidInput, "timestamp out of range for a DOS file"))
+}
+
+impl FilePermissions {
+    #[inline]
+    pub const fn from_flags(attributes: Flags) -> Self {
+        Self { attributes }
+    }
+
+    #[inline]
+    pub const fn flags(&self) -> Flags {
+        self.attributes
+    }
+
+    #[inline]
+    pub const fn set_flags(&mut self, attributes: Flags) {
+        self.attributes = attributes;
+    }
+
+    #[inline]
+    pub const fn readonly(&self) -> bool {
+        self.attributes.contains(Flags::R)
+    }
+
+    #[inline]
+    pub const fn set_readonly(&mut self, readonly: bool) {
+        self.attributes.set(Flags::R, readonly);
+    }
+}
+
+impl FileType {
+    #[inline]
+    pub fn is_dir(&self) -> bool {
+        self.attributes.contains(Flags::D)
+    }
+
+    #[inline]
+    pub fn is_file(&self) -> bool {
+        !self.attributes.intersects(Flags::D | Flags::V)
+    }
+
+    #[inline]
+    pub fn is_volume_label(&self) -> bool {
+        self.attributes.contains(Flags::V)
+    }
+
+    #[inline]
+    pub fn is_symlink(&self) -> bool {
+        false
+    }
+}
+
+pub fn readdir(path: *const u8) -> io::Result<ReadDir> {
+    let mut pattern = PathBuf::from_ptr(path)?;
+    pattern.push(b"*.*")?;
+
+    let inner = dos::fs::read_dir(pattern.as_ptr(), Flags::H | Flags::S | Flags::D)?;
+    Ok(ReadDir { inner })
+}
+
+/// Looks `path` up in its directory with FindFirst, so the file does not have to be opened.
+///
+/// Root directories have no directory entry, and neither has a bare drive such as `C:`, which
+/// stands for its current directory. Both are reported as a bare directory as long as their
+/// drive exists.
+pub fn stat(path: *const u8) -> io::Result<FileAttr> {
+    let mut path = PathBuf::from_ptr(path)?;
+    path.trim_end_separators();
+    let bytes = path.as_bytes();
+
+    if bytes.iter().any(|&byte| matches!(byte, b'*' | b'?')) {
+        return Err(io::Error::new_const(ErrorKind::InvalidInput, "path contains wildcards"));
+    }
+
+    let (drive, rest) = match bytes {
+        [letter, b':', rest @ ..] => (dos::drive::Drive::from_letter(*letter), rest),
+        rest => (Some(dos::drive::current()), rest),
+    };
+
+    let directory = match rest {
+        [] => bytes.len() == 2,
+        [separator] => is_separator(*separator),
+        _ => false,
+    };
+
+    if directory {
+        return match drive {
+            Some(drive) if drive.exists() => Ok(FileAttr { size: 0, attributes: Flags::D, modified: FileTime::EPOCH, accessed: None, created: None }),
+            _ => Err(dos::Error::InvalidDrive.into()),
+        };
+    }
+
+    match dos::fs::read_dir(path.as_ptr(), Flags::H | Flags::S | Flags::D)?.next() {
+        Some(entry) => Ok(FileAttr::from(&entry?)),
+        None => Err(dos::Error::FileNotFound.into()),
+    }
+}
+
+/// Sets the attributes of `path`, leaving out the directory and volume bits DOS refuses to set.
+pub fn set_perm(path: *const u8, perm: FilePermissions) -> io::Result<()> {
+    let mut path = PathBuf::from_ptr(path)?;
+    path.trim_end_separators();
+    Ok(dos::fs::set_flags(path.as_ptr(), perm.attributes & !(Flags::D | Flags::V))?)
+}
+
+#[inline]
+pub fn mkdir(path: *const u8) -> io::Result<()> {
+    Ok(dos::fs::create_dir(path)?)
+}
+
+#[inline]
+pub fn rmdir(path: *const u8) -> io::Result<()> {
+    Ok(dos::fs::remove_dir(path)?)
+}
+
+#[inline]
+pub fn unlink(path: *const u8) -> io::Result<()> {
+    Ok(dos::fs::remove_file(path)?)
+}
+
+/// Returns `true` if `path` names an existing directory.
+fn is_dir(path: *const u8) -> bool {
+    stat(path).is_ok_and(|attr| attr.file_type().is_dir())
+}
+
+/// Creates `path` and any missing parents, one component at a time.
+///
+/// DOS reports an existing directory as [`dos::Error::AccessDenied`], so a failed component
+/// is only an error if it is not a directory afterwards.
+pub fn mkdir_all(path: *const u8) -> io::Result<()> {
+    let path = PathBuf::from_ptr(path)?;
+    let bytes = path.as_bytes();
+    let root = if matches!(bytes, [_, b':', ..]) { 2 } else { 0 };
+
+    let is_component_end = |end: usize| match bytes.get(end) {
+        Some(&byte) => is_separator(byte),
+        None => !is_separator(bytes[end - 1]),
+    };
+
+    for end in (root + 1..=bytes.len()).filter(|&end| is_component_end(end)) {
+        let mut prefix = PathBuf::new();
+        prefix.push(&bytes[..end])?;
+
+        if let Err(error) = mkdir(prefix.as_ptr())
+            && !is_dir(prefix.as_ptr())
+        {
+            return Err(error);
+        }
+    }
+
+    Ok(())
+}
+
+/// Removes the contents of `path` depth first, then `path` itself.
+pub fn remove_dir_all(path: *const u8) -> io::Result<()> {
+    let mut child = PathBuf::from_ptr(path)?;
+
+    for entry in readdir(path)? {
+        let entry = entry?;
+        child.push(entry.file_name())?;
+
+        if entry.inner.is_dir() {
+            remove_dir_all(child.as_ptr())?;
+        } else {
+            unlink(child.as_ptr())?;
+        }
+
+        child.pop();
+    }
+
+    rmdir(path)
+}
@@ -1,6 +1,8 @@
-use core::arch::asm;
+use crate::io::ErrorKind;
+use bios::Registers;
 use core::cell::LazyCell;
 
+pub mod alloc;
 pub mod env_consts;
 pub mod fs;
 
@@ -21,9 +23,28 @@ pub struct Version {
 #[unsafe(no_mangle)]
 pub fn version() -> Version {
     static mut VERSION: LazyCell<Version> = LazyCell::new(|| {
-        let (major, minor);
-        unsafe { asm!("int 0x21", inlateout("ah") Service::Version as u8 => major, lateout("al") minor, options(nostack, preserves_flags)) }
-        Version { major, minor }
+        let mut regs = Registers { ax: (Service::Version as u16) << 8, ..Registers::new() };
+        unsafe { bios::int::<0x21>(&mut regs) };
+        Version { major: regs.al(), minor: regs.ah() }
     });
     unsafe { *VERSION }
 }
+
+/// Maps a DOS error onto the portable [`ErrorKind`] categories.
+pub fn decode_error_kind(error: &dos::Error) -> ErrorKind {
+    use dos::Error::*;
+
+    match error {
+        FileNotFound | PathNotFound | InvalidDrive | NoMoreFiles | BadNetworkPath | BadNetworkName => ErrorKind::NotFound,
+        AccessDenied | WriteProtected | InvalidPassword | NetworkAccessDenied => ErrorKind::PermissionDenied,
+        FileExists => ErrorKind::AlreadyExists,
+        InsufficientMemory => ErrorKind::OutOfMemory,
+        DiskFull | NetworkDiskFull => ErrorKind::StorageFull,
+        InvalidFunction | NetworkNotSupported | NetworkFunctionNotSupported => ErrorKind::Unsupported,
+        InvalidHandle | InvalidAccessCode | InvalidParameter => ErrorKind::InvalidInput,
+        InvalidFormat | InvalidData | DataError => ErrorKind::InvalidData,
+        SharingViolation | LockViolation | RemoveCurrentDirectory | NetworkBusy => ErrorKind::ResourceBusy,
+        EndOfInput => ErrorKind::UnexpectedEof,
+        _ => ErrorKind::Other,
+    }
+}
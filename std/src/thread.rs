@@ -0,0 +1,42 @@
+//! Native threads, of which DOS has exactly one.
+
+use crate::time::{Duration, Instant};
+
+/// Puts the current thread to sleep for at least the specified amount of time.
+///
+/// The BIOS wait (int 15h, AH=86h) is used where available, so interrupts keep being served
+/// and emulators can idle. It only has a resolution of 976 µs, so the last bit of the sleep
+/// and the whole sleep on machines without it are spent polling [`Instant`], which is accurate
+/// to about a microsecond. Sleeps across midnight are measured correctly.
+pub fn sleep(dur: Duration) {
+    sleep_from(Instant::now(), dur);
+}
+
+/// Puts the current thread to sleep until the specified deadline has passed.
+///
+/// Returns immediately if the deadline has already passed. See [`sleep`].
+pub fn sleep_until(deadline: Instant) {
+    let now = Instant::now();
+    sleep_from(now, deadline.saturating_duration_since(now));
+}
+
+fn sleep_from(start: Instant, dur: Duration) {
+    /// The part of a sleep left to polling, as the BIOS may round its wait up by a unit.
+    const SLACK: Duration = Duration::from_millis(2);
+
+    let mut bios = true;
+
+    loop {
+        let remaining = dur.saturating_sub(start.elapsed());
+        if remaining.is_zero() {
+            return;
+        }
+
+        if bios && remaining > SLACK {
+            let micros = u32::try_from((remaining - SLACK).as_micros()).unwrap_or(u32::MAX);
+            bios = bios::system::wait(micros).is_ok();
+        } else {
+            core::hint::spin_loop();
+        }
+    }
+}
@@ -1,6 +1,5 @@
 //! Temporal quantification.
 
-use core::arch::asm;
 use core::ops::{Add, AddAssign, Sub, SubAssign};
 use thiserror::Error;
 
@@ -38,16 +37,24 @@ pub use dos::time::*;
 /// [`UNIX_EPOCH`]: SystemTime::UNIX_EPOCH
 #[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
 pub struct SystemTime {
-    unix: u32,
+    /// Seconds since [`SystemTime::UNIX_EPOCH`].
+    secs: i64,
+    /// Always less than one second.
+    nanos: u32,
 }
 
+const NANOS_PER_SEC: u32 = 1_000_000_000;
+
 impl SystemTime {
     /// An anchor in time which can be used to create new [`SystemTime`] instances or
     /// learn about where in time a [`SystemTime`] lies.
-    pub const UNIX_EPOCH: Self = Self { unix: 0 };
+    pub const UNIX_EPOCH: Self = Self { secs: 0, nanos: 0 };
 
     /// Returns the system time corresponding to “now”.
     ///
+    /// DOS keeps the local time with a resolution of a hundredth of a second, which is taken
+    /// as UTC. See [`DateTime::now`].
+    ///
     /// # Examples
     /// ```
     /// use dos::time::SystemTime;
@@ -56,24 +63,37 @@ impl SystemTime {
     /// ```
     #[must_use]
     pub fn now() -> Self {
-        let (h, m, s): (u8, u8, u8);
-        unsafe { asm!("int 0x21", in("ah") 0x2Cu8, lateout("ch") h, lateout("cl") m, lateout("dh") s, options(nomem, nostack)) };
-        let dos = (h as u32) * 3600 + (m as u32) * 60 + (s as u32);
-        Self { unix: 315_532_800 + dos }
+        DateTime::now().into()
     }
 
     /// Returns <code>[Some]\(t)</code> where `t` is the time `self + duration` if `t` can be represented as
     /// [`SystemTime`] (which means it's inside the bounds of the underlying data structure), [`None`]
     /// otherwise.
     pub fn checked_add(&self, duration: Duration) -> Option<Self> {
-        self.unix.checked_add(duration.as_secs() as u32).map(|unix| Self { unix })
+        let mut secs = self.secs.checked_add_unsigned(duration.as_secs())?;
+        let mut nanos = self.nanos + duration.subsec_nanos();
+
+        if nanos >= NANOS_PER_SEC {
+            nanos -= NANOS_PER_SEC;
+            secs = secs.checked_add(1)?;
+        }
+
+        Some(Self { secs, nanos })
     }
 
     /// Returns <code>[Some]\(t)</code> where `t` is the time `self - duration` if `t` can be represented as
     /// [`SystemTime`] (which means it's inside the bounds of the underlying data structure), [`None`]
     /// otherwise.
     pub fn checked_sub(&self, duration: Duration) -> Option<SystemTime> {
-        self.unix.checked_sub(duration.as_secs() as u32).map(|unix| Self { unix })
+        let mut secs = self.secs.checked_sub_unsigned(duration.as_secs())?;
+        let mut nanos = self.nanos;
+
+        if nanos < duration.subsec_nanos() {
+            nanos += NANOS_PER_SEC;
+            secs = secs.checked_sub(1)?;
+        }
+
+        Some(Self { secs, nanos: nanos - duration.subsec_nanos() })
     }
 
     /// Returns the amount of time elapsed from an earlier point in time.
@@ -100,11 +120,18 @@ impl SystemTime {
     /// println!("{difference:?}")
     /// ```
     pub fn duration_since(&self, earlier: Self) -> Result<Duration, SystemTimeError> {
-        if self.unix >= earlier.unix {
-            Ok(Duration::from_secs((self.unix - earlier.unix) as u64))
+        if *self >= earlier { Ok(Self::difference(*self, earlier)) } else { Err(SystemTimeError(Self::difference(earlier, *self))) }
+    }
+
+    /// Returns `later - earlier`, where `later` is not earlier than `earlier`.
+    fn difference(later: Self, earlier: Self) -> Duration {
+        let (secs, nanos) = if later.nanos >= earlier.nanos {
+            (later.secs.abs_diff(earlier.secs), later.nanos - earlier.nanos)
         } else {
-            Err(SystemTimeError(Duration::from_secs((earlier.unix - self.unix) as u64)))
-        }
+            (later.secs.abs_diff(earlier.secs) - 1, later.nanos + NANOS_PER_SEC - earlier.nanos)
+        };
+
+        Duration::new(secs, nanos)
     }
 
     /// Returns the difference from this system time to the current clock time.
@@ -156,6 +183,30 @@ impl SubAssign<Duration> for SystemTime {
     }
 }
 
+impl From<DateTime> for SystemTime {
+    /// Converts a broken-down date and time, taking its local time as UTC like [`SystemTime::now`] does.
+    fn from(datetime: DateTime) -> Self {
+        Self { secs: datetime.to_unix() as i64, nanos: datetime.hundredths() as u32 * 10_000_000 }
+    }
+}
+
+impl TryFrom<SystemTime> for DateTime {
+    type Error = RangeError;
+
+    /// Converts to a broken-down date and time, truncating to hundredths. Fails before 1970 or after 9999.
+    fn try_from(time: SystemTime) -> Result<Self, Self::Error> {
+        let secs = u64::try_from(time.secs).map_err(|_| RangeError)?;
+        Self::from_unix(secs, (time.nanos / 10_000_000) as u8).ok_or(RangeError)
+    }
+}
+
+impl From<FileTime> for SystemTime {
+    /// Converts a file timestamp, taking its local time as UTC like [`SystemTime::now`] does.
+    fn from(time: FileTime) -> Self {
+        Self { secs: time.to_unix() as i64, nanos: 0 }
+    }
+}
+
 /// An error returned from the [`duration_since`] and [`elapsed`] methods on
 /// [`SystemTime`], used to learn how far in the opposite direction a system time
 /// lies.
@@ -175,3 +226,30 @@ impl SystemTimeError {
         self.0
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn date_time_round_trip() {
+        for datetime in [DateTime::new(1970, 1, 1, 0, 0, 0, 0), DateTime::new(1980, 1, 1, 0, 0, 0, 0), DateTime::new(2107, 12, 31, 23, 59, 59, 99)] {
+            let datetime = datetime.unwrap();
+            assert_eq!(DateTime::try_from(SystemTime::from(datetime)), Ok(datetime));
+        }
+    }
+
+    #[test]
+    fn date_time_truncates_to_hundredths() {
+        let time = SystemTime::UNIX_EPOCH + Duration::from_millis(1_999);
+        assert_eq!(DateTime::try_from(time), Ok(DateTime::new(1970, 1, 1, 0, 0, 1, 99).unwrap()));
+    }
+
+    #[test]
+    fn date_time_range() {
+        assert_eq!(DateTime::try_from(SystemTime::UNIX_EPOCH - Duration::from_nanos(1)), Err(RangeError));
+
+        let last = SystemTime::from(DateTime::new(9999, 12, 31, 23, 59, 59, 99).unwrap());
+        assert_eq!(DateTime::try_from(last + Duration::from_millis(10)), Err(RangeError));
+    }
+}